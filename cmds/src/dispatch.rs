@@ -1,6 +1,9 @@
-mod ilo;
+mod bmc;
+mod config;
 mod midi;
+mod settings;
 mod status;
+mod tasks;
 mod uptime;
 
 use kon_libs::{
@@ -10,20 +13,45 @@ use kon_libs::{
   PoiseCtx
 };
 
+pub use {
+  bmc::report as bmc_report,
+  midi::finish_conversions,
+  settings::guild_check
+};
+
 use {
-  ilo::ilo,
+  bmc::{
+    bmc,
+    ilo
+  },
+  config::config,
   midi::midi_to_wav,
+  settings::settings,
   status::status,
+  tasks::tasks,
   uptime::uptime
 };
 
+/// Old command names kept as aliases, mapped to the command they stand for
+const ALIASES: [(&str, &str); 1] = [("ilo", "bmc")];
+
+/// Qualified name with an alias swapped for its command, so both share metrics and the guild's on/off setting
+pub fn canonical_name(qualified_name: &str) -> String {
+  let (root, rest) = qualified_name.split_once(' ').unwrap_or((qualified_name, ""));
+  let root = ALIASES.iter().find(|(alias, _)| *alias == root).map_or(root, |(_, command)| command);
+
+  if rest.is_empty() { root.to_string() } else { format!("{root} {rest}") }
+}
+
 macro_rules! commands {
   ($($cmd:ident),*) => {
     vec![$($cmd()),*]
   }
 }
 
-pub fn register_cmds() -> Vec<poise::Command<KonData, KonError>> { commands!(deploy, ping, ilo, midi_to_wav, status, uptime) }
+pub fn register_cmds() -> Vec<poise::Command<KonData, KonError>> {
+  commands!(deploy, ping, bmc, config, ilo, midi_to_wav, settings, status, tasks, uptime)
+}
 
 /// Deploy the commands globally or in a guild
 #[poise::command(prefix_command, owners_only, guild_only)]
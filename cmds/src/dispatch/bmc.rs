@@ -0,0 +1,280 @@
+pub mod report;
+use report::Report;
+
+use {
+  kon_libs::{
+    BINARY_PROPERTIES,
+    HttpProfile,
+    IloHost,
+    KonData,
+    KonError,
+    KonResult,
+    RedfishTransport,
+    StatusCode,
+    embed_color
+  },
+  kon_redfish::{
+    AuthMode,
+    Credentials,
+    RedfishClient,
+    RedfishResult
+  },
+  kon_tokens::{
+    invalidate_secrets,
+    token_path
+  },
+  poise::{
+    CreateReply,
+    serenity_prelude::{
+      AutocompleteChoice,
+      CreateAutocompleteResponse,
+      CreateEmbed,
+      Timestamp
+    }
+  },
+  std::{
+    collections::HashMap,
+    sync::{
+      Arc,
+      LazyLock,
+      Mutex
+    }
+  },
+  tracing::debug
+};
+
+/// Name of the server when `[ilo]` has no hosts and the secrets' `ilo_ip` is used instead
+const LEGACY_HOSTNAME: &str = "POMNI";
+
+/// One client per host so sessions are reused between commands
+static CLIENTS: LazyLock<Mutex<HashMap<String, CachedClient>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct CachedClient {
+  host:   IloHost,
+  client: Arc<RedfishClient>
+}
+
+/// Configured host by name or the default one
+async fn find_host(server: Option<&str>) -> Option<IloHost> {
+  let config = BINARY_PROPERTIES.load();
+  if !config.ilo.hosts.is_empty() {
+    return config.ilo.host(server).cloned();
+  }
+
+  if server.is_some_and(|name| !name.eq_ignore_ascii_case(LEGACY_HOSTNAME)) {
+    return None;
+  }
+
+  Some(IloHost {
+    name:        LEGACY_HOSTNAME.to_string(),
+    address:     token_path().await.ilo_ip,
+    credentials: "default".to_string(),
+    auth:        AuthMode::Basic
+  })
+}
+
+/// Host for the `server` argument, tells the user if there's no such server
+async fn pick_host(
+  ctx: super::PoiseCtx<'_>,
+  server: Option<String>
+) -> KonResult<Option<IloHost>> {
+  let host = find_host(server.as_deref()).await;
+  if host.is_none() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("There's no server called `{}`", server.unwrap_or_default()))
+          .ephemeral(true)
+      )
+      .await?;
+  }
+
+  Ok(host)
+}
+
+async fn autocomplete_server<'a>(
+  _: super::PoiseCtx<'_>,
+  partial: &'a str
+) -> CreateAutocompleteResponse<'a> {
+  let config = BINARY_PROPERTIES.load();
+  let names = if config.ilo.hosts.is_empty() {
+    vec![LEGACY_HOSTNAME.to_string()]
+  } else {
+    config.ilo.hosts.iter().map(|host| host.name.clone()).collect()
+  };
+
+  let partial = partial.to_lowercase();
+  let choices: Vec<_> = names
+    .into_iter()
+    .filter(|name| name.to_lowercase().contains(&partial))
+    .map(|name| AutocompleteChoice::new(name.clone(), name))
+    .collect();
+
+  CreateAutocompleteResponse::new().set_choices(choices)
+}
+
+/// The host's client, replaced when its config or login changed
+async fn redfish_client(host: &IloHost) -> KonResult<Arc<RedfishClient>> {
+  let login = token_path()
+    .await
+    .ilo_credentials(&host.credentials)
+    .ok_or_else(|| format!("No BMC login called \"{}\" in the secrets for {}", host.credentials, host.name))?;
+  let credentials = Credentials::new(login.user, login.password);
+
+  let key = host.name.to_lowercase();
+  let mut clients = CLIENTS.lock().unwrap();
+  if let Some(cached) = clients.get(&key)
+    && cached.host == *host
+    && *cached.client.credentials() == credentials
+  {
+    return Ok(cached.client.clone());
+  }
+
+  // BMCs serve self-signed certificates
+  let client = Arc::new(RedfishClient::new(
+    RedfishTransport::new(HttpProfile::InsecureTls),
+    &host.address,
+    credentials,
+    host.auth
+  ));
+
+  if let Some(stale) = clients.insert(
+    key,
+    CachedClient {
+      host:   host.clone(),
+      client: client.clone()
+    }
+  ) {
+    tokio::spawn(async move { stale.client.logout().await });
+  }
+
+  Ok(client)
+}
+
+/// Run `fetch` against the host, once more with a fresh login if the password was rotated since the secrets were cached
+async fn bmc_data<T, F, Fut>(
+  host: &IloHost,
+  fetch: F
+) -> KonResult<T>
+where
+  F: Fn(Arc<RedfishClient>) -> Fut,
+  Fut: Future<Output = RedfishResult<T>>
+{
+  match fetch(redfish_client(host).await?).await {
+    Err(e) if e.status() == Some(StatusCode::UNAUTHORIZED) => {
+      debug!(host = %host.name, "Login was rejected, fetching the secrets again");
+      invalidate_secrets();
+      Ok(fetch(redfish_client(host).await?).await?)
+    },
+    result => Ok(result?)
+  }
+}
+
+fn embed_builder(
+  host: &IloHost,
+  color: i32,
+  report: Report
+) -> CreateEmbed {
+  let mut embed = CreateEmbed::new()
+    .color(color)
+    .timestamp(Timestamp::now())
+    .title(format!("{} - {}", host.name, report.title));
+
+  if let Some(d) = report.description {
+    embed = embed.description(d);
+  }
+
+  for (name, value, inline) in report.fields {
+    embed = embed.field(name, value, inline);
+  }
+
+  embed
+}
+
+/// Shared by every subcommand, only what's fetched and shown differs
+async fn reply<F, Fut>(
+  ctx: super::PoiseCtx<'_>,
+  server: Option<String>,
+  build: F
+) -> KonResult<()>
+where
+  F: Fn(Arc<RedfishClient>) -> Fut,
+  Fut: Future<Output = RedfishResult<Report>>
+{
+  let Some(host) = pick_host(ctx, server).await? else { return Ok(()) };
+  ctx.defer().await?;
+
+  let report = bmc_data(&host, build).await?;
+  ctx
+    .send(CreateReply::default().embed(embed_builder(&host, embed_color(ctx).await, report)))
+    .await?;
+
+  Ok(())
+}
+
+/// Retrieve data from the server's BMC (iLO, iDRAC or Supermicro)
+#[poise::command(
+  slash_command,
+  install_context = "Guild|User",
+  interaction_context = "Guild|BotDm|PrivateChannel",
+  subcommands("temperature", "power", "system", "logs")
+)]
+pub async fn bmc(_: super::PoiseCtx<'_>) -> KonResult<()> { Ok(()) }
+
+/// `/bmc` under its old name, see `canonical_name` for how the two are tied together
+pub fn ilo() -> poise::Command<KonData, KonError> {
+  let mut command = bmc();
+  command.name = "ilo".into();
+  command.qualified_name = "ilo".into();
+  command.description = Some("Retrieve data from the server's BMC, same as /bmc".into());
+
+  for subcommand in &mut command.subcommands {
+    subcommand.qualified_name = format!("ilo {}", subcommand.name).into();
+  }
+
+  command
+}
+
+/// Retrieve the server's temperature data
+#[poise::command(slash_command)]
+async fn temperature(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Server to ask, the default one if empty"]
+  #[autocomplete = "autocomplete_server"]
+  server: Option<String>
+) -> KonResult<()> {
+  reply(ctx, server, |client| async move { report::temperature(&client).await }).await
+}
+
+/// Retrieve the server's power data
+#[poise::command(slash_command)]
+async fn power(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Server to ask, the default one if empty"]
+  #[autocomplete = "autocomplete_server"]
+  server: Option<String>
+) -> KonResult<()> {
+  reply(ctx, server, |client| async move { report::power(&client).await }).await
+}
+
+/// Retrieve the server's system data
+#[poise::command(slash_command)]
+async fn system(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Server to ask, the default one if empty"]
+  #[autocomplete = "autocomplete_server"]
+  server: Option<String>
+) -> KonResult<()> {
+  reply(ctx, server, |client| async move { report::system(&client).await }).await
+}
+
+/// Retrieve the latest entries of the server's event log
+#[poise::command(slash_command)]
+async fn logs(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Server to ask, the default one if empty"]
+  #[autocomplete = "autocomplete_server"]
+  server: Option<String>
+) -> KonResult<()> {
+  reply(ctx, server, |client| async move { report::logs(&client).await }).await
+}
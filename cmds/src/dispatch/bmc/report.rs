@@ -0,0 +1,213 @@
+//! What the `/bmc` subcommands show, kept apart from Discord so it can be tested against the mock server.
+//!
+//! Every vendor ends up in the same embeds, the differences are handled here and in `kon_redfish`.
+
+use {
+  chrono::{
+    DateTime,
+    FixedOffset,
+    Utc
+  },
+  kon_redfish::{
+    ComputerSystem,
+    EventService,
+    LogEntry,
+    LogService,
+    PostState,
+    Power,
+    RedfishClient,
+    RedfishResult,
+    Thermal
+  },
+  tracing::debug
+};
+
+/// Sensors worth showing and what to call them, the rest are left out to keep the embed short
+const SENSORS: [(&str, &str); 8] = [
+  // iLO
+  ("01-Inlet Ambient", "Inlet Ambient"),
+  ("04-P1 DIMM 1-6", "P1 DIMM 1-6"),
+  ("14-Chipset Zone", "Chipset Zone"),
+  // iDRAC
+  ("System Board Inlet Temp", "Inlet Ambient"),
+  ("System Board Exhaust Temp", "Exhaust"),
+  // Supermicro
+  ("Inlet Temp", "Inlet Ambient"),
+  ("System Temp", "System"),
+  ("Peripheral Temp", "Peripheral")
+];
+
+/// Embed content for one subcommand
+pub struct Report {
+  pub title:       &'static str,
+  pub description: Option<String>,
+  pub fields:      Vec<(String, String, bool)>
+}
+
+pub async fn temperature(client: &RedfishClient) -> RedfishResult<Report> {
+  Ok(Report {
+    title:       "Temperatures",
+    description: None,
+    fields:      temperature_fields(&client.thermal().await?)
+  })
+}
+
+pub async fn power(client: &RedfishClient) -> RedfishResult<Report> {
+  Ok(Report {
+    title:       "Power",
+    description: Some(power_description(&client.power().await?)),
+    fields:      Vec::new()
+  })
+}
+
+pub async fn system(client: &RedfishClient) -> RedfishResult<Report> {
+  let (system, events) = tokio::join!(client.system(), client.event_service());
+  let (description, fields) = system_report(&system?, &events?);
+
+  Ok(Report {
+    title: "System",
+    description: Some(description),
+    fields
+  })
+}
+
+/// The latest entries of the IML on iLO, the lifecycle log on iDRAC or the event log on Supermicro
+pub async fn logs(client: &RedfishClient) -> RedfishResult<Report> {
+  let service = client.event_log().await?;
+
+  Ok(Report {
+    title:       log_title(&service),
+    description: Some(log_description(&client.latest_log_entries(&service, 5).await?)),
+    fields:      Vec::new()
+  })
+}
+
+/// Every vendor's timestamps the same way, in UTC since they log with different offsets
+fn fmt_dt(dt: DateTime<FixedOffset>) -> String { dt.with_timezone(&Utc).format("%d/%m/%Y %H:%M:%S").to_string() }
+
+fn sensor_label(name: &str) -> Option<&'static str> { SENSORS.iter().find(|(sensor, _)| *sensor == name).map(|(_, label)| *label) }
+
+fn temperature_fields(data: &Thermal) -> Vec<(String, String, bool)> {
+  let mut tempdata = String::new();
+  let mut fandata = String::new();
+
+  // A BMC without any of the known sensors gets all of them
+  let known = data.temperatures.iter().any(|temp| sensor_label(&temp.name).is_some());
+
+  for temp in &data.temperatures {
+    let Some(reading) = temp.reading_celsius else { continue };
+    let label = sensor_label(&temp.name);
+    if reading == 0.0 || (known && label.is_none()) {
+      continue;
+    }
+
+    let name = label.unwrap_or(temp.name.as_str());
+    tempdata.push_str(&format!("**{name}:** `{reading}°C`\n"));
+  }
+  for fan in &data.fans {
+    let Some(reading) = fan.reading else { continue };
+    if reading == 0.0 {
+      continue;
+    }
+
+    let unit = match fan.reading_units.as_deref() {
+      Some("RPM") => " RPM",
+      _ => "%"
+    };
+    fandata.push_str(&format!("**{}:** `{reading}{unit}`\n", fan.name));
+  }
+
+  vec![("Temperatures".to_string(), tempdata, false), ("Fans".to_string(), fandata, false)]
+}
+
+fn power_description(data: &Power) -> String {
+  let summary = data.summary();
+  let metrics = summary.power_metrics.unwrap_or_default();
+  let watts = |value: Option<f64>| value.map_or_else(|| "Unknown".to_string(), |w| format!("{w}w"));
+
+  let mut powerdata = String::new();
+
+  powerdata.push_str(&format!("**Power Capacity:** `{}`\n", watts(summary.power_capacity_watts)));
+  powerdata.push_str(&format!("**Power Consumed:** `{}`\n", watts(summary.power_consumed_watts)));
+  powerdata.push_str(&format!("**Average Power:** `{}`\n", watts(metrics.average_consumed_watts)));
+  powerdata.push_str(&format!("**Max Consumed:** `{}`\n", watts(metrics.max_consumed_watts)));
+  powerdata.push_str(&format!("**Min Consumed:** `{}`", watts(metrics.min_consumed_watts)));
+
+  powerdata
+}
+
+fn system_report(
+  system: &ComputerSystem,
+  events: &EventService
+) -> (String, Vec<(String, String, bool)>) {
+  let post_state = system.post_state();
+  let post_label = match post_state {
+    PostState::Finished => "Finished POST",
+    PostState::InPost => "In POST (Booting)",
+    PostState::PowerOff => "Powered off",
+    PostState::Unknown => "Unknown State"
+  };
+  if post_state != PostState::Finished {
+    debug!(?post_state, "Server isn't done with POST");
+  }
+
+  // Not every event service has a status, the system's rollup is the next best thing
+  let health = events
+    .status
+    .as_ref()
+    .and_then(|status| status.health.as_deref())
+    .or_else(|| {
+      system
+        .status
+        .as_ref()
+        .and_then(|status| status.health_rollup.as_deref().or(status.health.as_deref()))
+    })
+    .unwrap_or("Unknown");
+  let unknown = || "Unknown".to_string();
+
+  let mut data = String::new();
+  data.push_str(&format!("**Health:** `{health}`\n"));
+  data.push_str(&format!("**POST:** `{post_label}`\n"));
+  data.push_str(&format!("**Power:** `{}`\n", system.power_state.clone().unwrap_or_else(unknown)));
+  data.push_str(&format!("**Model:** `{}`", system.model.clone().unwrap_or_else(unknown)));
+
+  let cpu = system.processor_summary.clone().unwrap_or_default();
+  let ram = system.total_memory_gib().map_or_else(unknown, |gib| format!("{gib} GB"));
+
+  (
+    data,
+    vec![
+      (
+        format!("CPU ({}x)", cpu.count.unwrap_or_default()),
+        cpu.model.unwrap_or_else(unknown).trim().to_string(),
+        true
+      ),
+      ("RAM".to_string(), ram, true),
+    ]
+  )
+}
+
+fn log_title(service: &LogService) -> &'static str {
+  match service.id.to_lowercase().as_str() {
+    "iml" => "IML",
+    "lclog" => "Lifecycle Log",
+    "sel" => "SEL",
+    _ => "Event Log"
+  }
+}
+
+/// Newest first, iLO lists the oldest entry first and iDRAC the newest
+fn log_description(entries: &[LogEntry]) -> String {
+  let mut log_entries = String::new();
+
+  for entry in entries {
+    let dt = entry.created_at().map(fmt_dt).unwrap_or_else(|| "Unknown".to_string());
+    log_entries.push_str(&format!(
+      "**[{}:{dt}]:** {}\n",
+      entry.severity.as_deref().unwrap_or("Unknown"),
+      entry.message.as_deref().unwrap_or_default()
+    ));
+  }
+
+  log_entries
+}
@@ -0,0 +1,76 @@
+use {
+  kon_libs::{
+    BINARY_PROPERTIES,
+    KonResult,
+    log_filter,
+    set_log_filter
+  },
+  poise::{
+    CreateReply,
+    serenity_prelude::{
+      CreateEmbed,
+      Timestamp
+    }
+  }
+};
+
+/// Manage the bot's runtime configuration
+#[poise::command(
+  slash_command,
+  owners_only,
+  install_context = "Guild|User",
+  interaction_context = "Guild|BotDm|PrivateChannel",
+  subcommands("reload", "log")
+)]
+pub async fn config(_: super::PoiseCtx<'_>) -> KonResult<()> { Ok(()) }
+
+/// Reload the config file without restarting the bot
+#[poise::command(slash_command, owners_only)]
+async fn reload(ctx: super::PoiseCtx<'_>) -> KonResult<()> {
+  let reply = match BINARY_PROPERTIES.reload() {
+    Ok(config) => {
+      let developers = config.developers.iter().map(|id| format!("<@{id}>")).collect::<Vec<String>>().join(", ");
+
+      CreateReply::default().embed(
+        CreateEmbed::new()
+          .color(config.embed_color)
+          .title("Config reloaded")
+          .timestamp(Timestamp::now())
+          .description(
+            [
+              format!("**Environment:** `{}`", config.env),
+              format!("**Ready notify:** <#{}>", config.ready_notify),
+              format!("**RSS channel:** <#{}>", config.rss_channel),
+              format!("**Logs:** <#{}>", config.kon_logs),
+              format!("**Developers:** {developers}")
+            ]
+            .join("\n")
+          )
+      )
+    },
+    Err(y) => CreateReply::default().content(format!("Config wasn't reloaded, the current one is kept:```\n{y}\n```"))
+  };
+
+  ctx.send(reply.ephemeral(true)).await?;
+
+  Ok(())
+}
+
+/// Show or change the log filter until the next restart or config reload
+#[poise::command(slash_command, owners_only)]
+async fn log(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Filter directives, e.g. info,kon_tasks=debug"] filter: Option<String>
+) -> KonResult<()> {
+  let content = match filter {
+    Some(filter) => match set_log_filter(&filter) {
+      Ok(()) => format!("Log filter is now `{filter}`"),
+      Err(y) => format!("Log filter wasn't changed:```\n{y}\n```")
+    },
+    None => format!("Log filter is `{}`", log_filter().unwrap_or_default())
+  };
+
+  ctx.send(CreateReply::default().content(content).ephemeral(true)).await?;
+
+  Ok(())
+}
@@ -3,7 +3,8 @@ use {
     KonError,
     KonResult,
     format_bytes,
-    mention_dev
+    mention_dev,
+    metrics::MIDI_CONVERSION_DURATION
   },
   poise::{
     CreateReply,
@@ -19,10 +20,69 @@ use {
       remove_file,
       write
     },
-    os::unix::fs::MetadataExt
+    os::unix::fs::MetadataExt,
+    sync::Mutex
+  },
+  tokio::time::{
+    Duration,
+    Instant,
+    sleep
+  },
+  tracing::{
+    info,
+    warn
   }
 };
 
+const SF2_PATH: &str = "/tmp/FluidR3_GM.sf2";
+
+/// Files belonging to conversions that are still in progress
+static IN_FLIGHT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Registers a conversion's files and deletes them once it's done, whether it succeeded or not
+struct TempFiles(Vec<String>);
+
+impl TempFiles {
+  fn new() -> Self { Self(Vec::new()) }
+
+  fn track(
+    &mut self,
+    path: &str
+  ) {
+    IN_FLIGHT.lock().unwrap().push(path.to_string());
+    self.0.push(path.to_string());
+  }
+}
+
+impl Drop for TempFiles {
+  fn drop(&mut self) {
+    let mut in_flight = IN_FLIGHT.lock().unwrap();
+    for path in &self.0 {
+      let _ = remove_file(path);
+      if let Some(i) = in_flight.iter().position(|p| p == path) {
+        in_flight.swap_remove(i);
+      }
+    }
+  }
+}
+
+/// Wait for the running conversions to wrap up, then delete whatever they left behind
+pub async fn finish_conversions(deadline: Duration) {
+  let started = Instant::now();
+  while !IN_FLIGHT.lock().unwrap().is_empty() && started.elapsed() < deadline {
+    sleep(Duration::from_millis(250)).await;
+  }
+
+  let leftovers: Vec<String> = IN_FLIGHT.lock().unwrap().drain(..).collect();
+  if !leftovers.is_empty() {
+    warn!("Removing files of {} unfinished conversion(s)", leftovers.len());
+  }
+
+  for path in leftovers.iter().map(String::as_str).chain([SF2_PATH]) {
+    let _ = remove_file(path);
+  }
+}
+
 /// Convert MIDI file to WAV
 #[poise::command(
   context_menu_command = "MIDI -> WAV",
@@ -58,19 +118,23 @@ pub async fn midi_to_wav(
   };
 
   let midi_path = &message.attachments[0].filename;
-  write(midi_path, bytes)?;
-
   let wav_path = re.replace(midi_path, ".wav");
 
-  let sf2_path = "/tmp/FluidR3_GM.sf2";
-  write(sf2_path, include_bytes!("../../../libs/assets/FluidR3_GM.sf2"))?;
+  let mut temp_files = TempFiles::new();
+  temp_files.track(midi_path);
+  temp_files.track(&wav_path);
+  write(midi_path, bytes)?;
+
+  write(SF2_PATH, include_bytes!("../../../libs/assets/FluidR3_GM.sf2"))?;
 
+  let timer = MIDI_CONVERSION_DURATION.start_timer();
   let output = std::process::Command::new("fluidsynth")
-    .args(["-ni", sf2_path, midi_path, "-F", &wav_path])
+    .args(["-ni", SF2_PATH, midi_path, "-F", &wav_path])
     .output();
+  timer.observe_duration();
 
   // Just to add an info to console to tell what the bot is doing when MIDI file is downloaded.
-  println!("Discord[{}]: Processing MIDI file: \"{midi_path}\"", ctx.command().qualified_name);
+  info!(command = %ctx.command().qualified_name, "Processing MIDI file: \"{midi_path}\"");
 
   match output {
     Ok(_) => {
@@ -79,9 +143,9 @@ pub async fn midi_to_wav(
         .await;
 
       if reply.is_err() {
-        println!(
-          "Discord[{}]: Processed file couldn't be uploaded back to Discord channel due to upload limit",
-          ctx.command().qualified_name
+        warn!(
+          command = %ctx.command().qualified_name,
+          "Processed file couldn't be uploaded back to Discord channel due to upload limit"
         );
 
         ctx
@@ -92,12 +156,7 @@ pub async fn midi_to_wav(
           )))
           .await?;
       } else if reply.is_ok() {
-        println!(
-          "Discord[{}]: Processed file uploaded back to Discord channel",
-          ctx.command().qualified_name
-        );
-        remove_file(midi_path)?;
-        remove_file(&*wav_path)?;
+        info!(command = %ctx.command().qualified_name, "Processed file uploaded back to Discord channel");
       }
     },
     Err(y) => {
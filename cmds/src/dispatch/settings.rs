@@ -0,0 +1,243 @@
+use super::canonical_name;
+
+use {
+  kon_libs::{
+    GuildSettings,
+    KonResult,
+    embed_color
+  },
+  poise::{
+    CreateReply,
+    serenity_prelude::{
+      CreateEmbed,
+      GuildChannel,
+      Role
+    }
+  },
+  tracing::warn
+};
+
+/// Commands that can't be switched off, otherwise there's no way back
+const ALWAYS_ENABLED: [&str; 1] = ["settings"];
+
+fn settings_embed(
+  settings: &GuildSettings,
+  color: i32
+) -> CreateEmbed {
+  let or_default = |value: Option<String>| value.unwrap_or_else(|| "`Default`".to_string());
+
+  let disabled = settings
+    .features
+    .iter()
+    .filter(|(_, enabled)| !**enabled)
+    .map(|(command, _)| format!("`/{command}`"))
+    .collect::<Vec<String>>();
+
+  let roles = settings.allowed_roles.iter().map(|id| format!("<@&{id}>")).collect::<Vec<String>>();
+
+  CreateEmbed::new().color(color).title("Server Settings").description(
+    [
+      format!("**Log channel:** {}", or_default(settings.log_channel.map(|id| format!("<#{id}>")))),
+      format!("**Embed color:** {}", or_default(settings.embed_color.map(|c| format!("`#{c:06X}`")))),
+      format!(
+        "**Disabled commands:** {}",
+        if disabled.is_empty() {
+          "`None`".to_string()
+        } else {
+          disabled.join(", ")
+        }
+      ),
+      format!(
+        "**Allowed roles:** {}",
+        if roles.is_empty() { "`Everyone`".to_string() } else { roles.join(", ") }
+      )
+    ]
+    .join("\n")
+  )
+}
+
+/// Load this guild's settings, apply the change and save them back
+async fn update(
+  ctx: super::PoiseCtx<'_>,
+  change: impl FnOnce(&mut GuildSettings)
+) -> KonResult<()> {
+  let guild_id = ctx.guild_id().ok_or("Settings are only available in servers")?.get();
+  let mut settings = (*ctx.data().guild_settings(guild_id).await?).clone();
+  change(&mut settings);
+  ctx.data().save_guild_settings(guild_id, settings).await?;
+
+  show_inner(ctx).await
+}
+
+async fn show_inner(ctx: super::PoiseCtx<'_>) -> KonResult<()> {
+  let guild_id = ctx.guild_id().ok_or("Settings are only available in servers")?.get();
+  let settings = ctx.data().guild_settings(guild_id).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(settings_embed(&settings, embed_color(ctx).await))
+        .ephemeral(true)
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Manage the bot's settings for this server
+#[poise::command(
+  slash_command,
+  guild_only,
+  install_context = "Guild",
+  interaction_context = "Guild",
+  default_member_permissions = "MANAGE_GUILD",
+  required_permissions = "MANAGE_GUILD",
+  subcommands("show", "logs", "color", "feature", "role", "reset")
+)]
+pub async fn settings(_: super::PoiseCtx<'_>) -> KonResult<()> { Ok(()) }
+
+/// Show this server's settings
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn show(ctx: super::PoiseCtx<'_>) -> KonResult<()> { show_inner(ctx).await }
+
+/// Set the channel command errors get reported to, leave empty to turn it off
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn logs(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Channel for error reports"] channel: Option<GuildChannel>
+) -> KonResult<()> {
+  update(ctx, |settings| settings.log_channel = channel.map(|c| c.id.get())).await
+}
+
+/// Set the embed color, leave empty to use the default one
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn color(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Hex color, e.g. #5A99C7"] hex: Option<String>
+) -> KonResult<()> {
+  let color = match hex {
+    Some(hex) => match i32::from_str_radix(hex.trim_start_matches('#'), 16) {
+      Ok(color) if (0..=0xFFFFFF).contains(&color) => Some(color),
+      _ => {
+        ctx
+          .send(CreateReply::default().content(format!("`{hex}` isn't a valid hex color")).ephemeral(true))
+          .await?;
+        return Ok(());
+      }
+    },
+    None => None
+  };
+
+  update(ctx, |settings| settings.embed_color = color).await
+}
+
+/// Turn a command on or off in this server
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn feature(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Command name, e.g. status"] command: String,
+  #[description = "Whether the command can be used"] enabled: bool
+) -> KonResult<()> {
+  let name = command.trim_start_matches('/').to_lowercase();
+
+  // Aliases like `/ilo` are stored under the command they stand for
+  let command = ctx
+    .framework()
+    .options()
+    .commands
+    .iter()
+    .find(|c| c.name == name)
+    .map(|c| canonical_name(&c.qualified_name))
+    .filter(|command| !ALWAYS_ENABLED.contains(&command.as_str()));
+
+  let Some(command) = command else {
+    ctx
+      .send(CreateReply::default().content(format!("`/{name}` can't be toggled")).ephemeral(true))
+      .await?;
+    return Ok(());
+  };
+
+  update(ctx, |settings| {
+    if enabled {
+      settings.features.remove(&command);
+    } else {
+      settings.features.insert(command, false);
+    }
+  })
+  .await
+}
+
+/// Allow or disallow a role to use the bot, no allowed roles means everyone can
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn role(
+  ctx: super::PoiseCtx<'_>,
+  #[description = "Role to change"] role: Role,
+  #[description = "Whether members with this role can use the bot"] allowed: bool
+) -> KonResult<()> {
+  update(ctx, |settings| {
+    if allowed {
+      settings.allowed_roles.insert(role.id.get());
+    } else {
+      settings.allowed_roles.remove(&role.id.get());
+    }
+  })
+  .await
+}
+
+/// Reset this server's settings back to defaults
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn reset(ctx: super::PoiseCtx<'_>) -> KonResult<()> { update(ctx, |settings| *settings = GuildSettings::default()).await }
+
+/// Global check that applies the guild's feature toggles and allowed roles,
+/// owners-only commands skip it so a server can't lock the owners out
+pub async fn guild_check(ctx: super::PoiseCtx<'_>) -> KonResult<bool> {
+  let Some(guild_id) = ctx.guild_id() else {
+    return Ok(true);
+  };
+
+  let command = canonical_name(&ctx.command().qualified_name)
+    .split_whitespace()
+    .next()
+    .unwrap_or_default()
+    .to_string();
+  if ctx.command().owners_only || ALWAYS_ENABLED.contains(&command.as_str()) {
+    return Ok(true);
+  }
+
+  // Better to ignore the settings for a while than to take every command down with Redis
+  let settings = match ctx.data().guild_settings(guild_id.get()).await {
+    Ok(settings) => settings,
+    Err(e) => {
+      warn!("Couldn't load the settings for guild {guild_id}, allowing /{command}: {e}");
+      return Ok(true);
+    }
+  };
+
+  if !settings.is_enabled(&command) {
+    ctx
+      .send(CreateReply::default().content("This command is disabled in this server").ephemeral(true))
+      .await?;
+    return Ok(false);
+  }
+
+  if !settings.allowed_roles.is_empty() {
+    let member = ctx.author_member().await;
+    let is_manager = member.as_ref().and_then(|m| m.permissions).is_some_and(|p| p.manage_guild());
+    let has_role = member
+      .as_ref()
+      .is_some_and(|m| m.roles.iter().any(|r| settings.allowed_roles.contains(&r.get())));
+
+    if !is_manager && !has_role {
+      ctx
+        .send(
+          CreateReply::default()
+            .content("You don't have a role that's allowed to use this bot here")
+            .ephemeral(true)
+        )
+        .await?;
+      return Ok(false);
+    }
+  }
+
+  Ok(true)
+}
@@ -10,16 +10,17 @@ use {
 };
 
 use kon_libs::{
-  BINARY_PROPERTIES,
   HttpClient,
-  KonResult
+  KonResult,
+  embed_color
 };
 
 async fn pms_serverstatus(url: &str) -> KonResult<Vec<(String, Vec<Value>)>> {
-  let client = HttpClient::new();
-  let req = client.get(url, "PMS-Status").await?;
-
-  let response = req.json::<HashMap<String, Value>>().await?;
+  let response = HttpClient::shared()
+    .get(url)
+    .user_agent("PMS-Status")
+    .json::<HashMap<String, Value>>()
+    .await?;
   let data = response["data"].as_array().unwrap();
 
   let mut servers = Vec::new();
@@ -86,7 +87,7 @@ pub async fn status(_: super::PoiseCtx<'_>) -> KonResult<()> { Ok(()) }
 async fn wg(ctx: super::PoiseCtx<'_>) -> KonResult<()> {
   let pms_asia = token_path().await.wg_pms;
   let pms_eu = pms_asia.replace("asia", "eu");
-  let embed = CreateEmbed::new().color(BINARY_PROPERTIES.embed_color);
+  let embed = CreateEmbed::new().color(embed_color(ctx).await);
 
   let (servers_asia, servers_eu) = join!(pms_serverstatus(&pms_asia), pms_serverstatus(&pms_eu));
   let joined_pms_servers = [servers_eu.unwrap(), servers_asia.unwrap()].concat();
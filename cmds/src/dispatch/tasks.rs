@@ -0,0 +1,195 @@
+use {
+  kon_libs::{
+    KonResult,
+    embed_color
+  },
+  kon_tasks::{
+    TaskInfo,
+    scheduler
+  },
+  poise::{
+    CreateReply,
+    serenity_prelude::{
+      ButtonStyle,
+      ComponentInteractionCollector,
+      CreateActionRow,
+      CreateButton,
+      CreateEmbed,
+      CreateInteractionResponse,
+      CreateInteractionResponseMessage,
+      Timestamp
+    }
+  },
+  std::time::Duration
+};
+
+// Discord only allows 5 rows of buttons per message
+const MAX_ROWS: usize = 5;
+
+fn fmt_time(
+  time: Option<chrono::DateTime<chrono::Utc>>,
+  fallback: &str
+) -> String {
+  match time {
+    Some(time) => format!("<t:{}:R>", time.timestamp()),
+    None => format!("`{fallback}`")
+  }
+}
+
+fn task_field(task: &TaskInfo) -> (String, String, bool) {
+  let state = if task.paused {
+    format!("{} (Paused)", task.state)
+  } else {
+    task.state.to_string()
+  };
+
+  let mut value = [
+    format!("**State:** `{state}`"),
+    format!("**Last run:** {}", fmt_time(task.stats.last_run, "Never")),
+    format!("**Next run:** {}", fmt_time(task.stats.next_run, "Not scheduled")),
+    format!("**Runs:** `{}`", task.stats.run_count)
+  ]
+  .join("\n");
+
+  if let Some(error) = &task.stats.last_error {
+    let error: String = error.chars().take(300).collect();
+    value.push_str(&format!("\n**Last error:** ```\n{error}\n```"));
+  }
+
+  (task.name.to_string(), value, false)
+}
+
+fn tasks_embed(
+  color: i32,
+  tasks: &[TaskInfo]
+) -> CreateEmbed {
+  let embed = CreateEmbed::new().color(color).title("Background Tasks").timestamp(Timestamp::now());
+
+  if tasks.is_empty() {
+    return embed.description("No tasks are registered");
+  }
+
+  embed.fields(tasks.iter().map(task_field))
+}
+
+fn task_buttons(
+  ctx_id: u64,
+  tasks: &[TaskInfo]
+) -> Vec<CreateActionRow> {
+  tasks
+    .iter()
+    .take(MAX_ROWS)
+    .map(|task| {
+      let toggle = if task.paused {
+        CreateButton::new(format!("{ctx_id}:resume:{}", task.name))
+          .label(format!("Resume {}", task.name))
+          .style(ButtonStyle::Success)
+      } else {
+        CreateButton::new(format!("{ctx_id}:pause:{}", task.name))
+          .label(format!("Pause {}", task.name))
+          .style(ButtonStyle::Secondary)
+      };
+
+      let trigger = CreateButton::new(format!("{ctx_id}:trigger:{}", task.name))
+        .label(format!("Run {} now", task.name))
+        .style(ButtonStyle::Primary)
+        .disabled(!task.scheduled);
+
+      CreateActionRow::Buttons(vec![toggle, trigger])
+    })
+    .collect()
+}
+
+/// Inspect and control the background tasks
+#[poise::command(
+  slash_command,
+  owners_only,
+  install_context = "Guild|User",
+  interaction_context = "Guild|BotDm|PrivateChannel"
+)]
+pub async fn tasks(ctx: super::PoiseCtx<'_>) -> KonResult<()> {
+  let ctx_id = ctx.id();
+  let author = ctx.author().id;
+  let color = embed_color(ctx).await;
+  let tasks = scheduler().tasks();
+
+  let reply = ctx
+    .send(
+      CreateReply::default()
+        .embed(tasks_embed(color, &tasks))
+        .components(task_buttons(ctx_id, &tasks))
+    )
+    .await?;
+
+  while let Some(press) = ComponentInteractionCollector::new(ctx)
+    .filter(move |press| press.data.custom_id.starts_with(&format!("{ctx_id}:")))
+    .timeout(Duration::from_secs(300))
+    .await
+  {
+    // The message is visible to everyone in the channel, only the owner who ran it gets to press
+    if press.user.id != author {
+      press
+        .create_response(
+          ctx.serenity_context(),
+          CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+              .content("Only whoever ran this command can use these buttons")
+              .ephemeral(true)
+          )
+        )
+        .await?;
+      continue;
+    }
+
+    let mut parts = press.data.custom_id.splitn(3, ':').skip(1);
+    let (Some(action), Some(name)) = (parts.next(), parts.next()) else {
+      continue;
+    };
+
+    if !matches!(action, "pause" | "resume" | "trigger") {
+      continue;
+    }
+
+    // Pausing waits for a running task to stop, which can take longer than Discord waits for an answer
+    press
+      .create_response(ctx.serenity_context(), CreateInteractionResponse::Acknowledge)
+      .await?;
+
+    let result = match action {
+      "pause" => scheduler().pause(name).await,
+      "resume" => scheduler().resume(name),
+      _ => scheduler().trigger(name)
+    };
+
+    match result {
+      Ok(()) => {
+        let tasks = scheduler().tasks();
+        reply
+          .edit(
+            ctx,
+            CreateReply::default()
+              .embed(tasks_embed(color, &tasks))
+              .components(task_buttons(ctx_id, &tasks))
+          )
+          .await?;
+      },
+      Err(e) => {
+        ctx
+          .send(CreateReply::default().content(format!("Couldn't {action} `{name}`: {e}")).ephemeral(true))
+          .await?;
+      }
+    }
+  }
+
+  // Buttons stop working once the collector times out, so take them off
+  reply
+    .edit(
+      ctx,
+      CreateReply::default()
+        .embed(tasks_embed(color, &scheduler().tasks()))
+        .components(Vec::new())
+    )
+    .await?;
+
+  Ok(())
+}
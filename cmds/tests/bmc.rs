@@ -0,0 +1,287 @@
+use {
+  kon_cmds::bmc_report::{
+    Report,
+    logs,
+    power,
+    system,
+    temperature
+  },
+  kon_libs::{
+    HttpProfile,
+    RedfishTransport
+  },
+  kon_redfish::{
+    AuthMode,
+    Credentials,
+    RedfishClient,
+    RedfishError
+  },
+  kon_redfish_mock::{
+    Fixture,
+    LongLog,
+    MockServer,
+    PASSWORD,
+    StatusCode,
+    USER
+  }
+};
+
+fn field<'a>(
+  report: &'a Report,
+  name: &str
+) -> &'a str {
+  report
+    .fields
+    .iter()
+    .find(|(field, ..)| field == name)
+    .map(|(_, value, _)| value.as_str())
+    .unwrap_or_else(|| panic!("{} has no {name} field", report.title))
+}
+
+/// Every `**Label:** `value`` line as `(label, value)`
+fn labelled(text: &str) -> Vec<(&str, &str)> {
+  text
+    .lines()
+    .map(|line| {
+      line
+        .strip_prefix("**")
+        .and_then(|line| line.split_once(":** `"))
+        .and_then(|(label, value)| Some((label, value.strip_suffix('`')?)))
+        .unwrap_or_else(|| panic!("\"{line}\" isn't a labelled value"))
+    })
+    .collect()
+}
+
+fn value<'a>(
+  text: &'a str,
+  label: &str
+) -> &'a str {
+  labelled(text)
+    .into_iter()
+    .find(|(name, _)| *name == label)
+    .map(|(_, value)| value)
+    .unwrap_or_else(|| panic!("No {label} in \"{text}\""))
+}
+
+/// Every `**[Severity:time]:** message` line as `(severity, time, message)`
+fn log_lines(text: &str) -> Vec<(&str, &str, &str)> {
+  text
+    .lines()
+    .map(|line| {
+      line
+        .strip_prefix("**[")
+        .and_then(|line| line.split_once("]:** "))
+        .and_then(|(head, message)| head.split_once(':').map(|(severity, time)| (severity, time, message)))
+        .unwrap_or_else(|| panic!("\"{line}\" isn't a log line"))
+    })
+    .collect()
+}
+
+/// What each fixture should report, down to the values its vendor's quirks decide
+struct Vendor {
+  fixture:      Fixture,
+  auth:         AuthMode,
+  /// Known sensors get the shared labels, unknown ones and zero readings are left out
+  temperatures: &'static [(&'static str, &'static str)],
+  /// iLO reports fan speed as a percentage, iDRAC and Supermicro in RPM
+  fans:         &'static [(&'static str, &'static str)],
+  capacity:     &'static str,
+  consumed:     &'static str,
+  /// Supermicro has no event service status, so it comes from the system's rollup
+  health:       &'static str,
+  model:        &'static str,
+  cpu:          (&'static str, &'static str),
+  ram:          &'static str,
+  log_title:    &'static str,
+  /// Newest first and in UTC, whichever order and offset the BMC uses
+  logs:         &'static [(&'static str, &'static str)],
+  newest_log:   &'static str
+}
+
+const VENDORS: [Vendor; 4] = [
+  Vendor {
+    fixture:      Fixture::Ilo5,
+    auth:         AuthMode::Session,
+    temperatures: &[("Inlet Ambient", "22°C"), ("P1 DIMM 1-6", "31°C"), ("Chipset Zone", "45°C")],
+    fans:         &[("Fan 1", "27%"), ("Fan 2", "29%")],
+    capacity:     "1000w",
+    consumed:     "187w",
+    health:       "OK",
+    model:        "ProLiant DL380 Gen10",
+    cpu:          ("CPU (2x)", "Intel(R) Xeon(R) Silver 4214 CPU @ 2.20GHz"),
+    ram:          "128 GB",
+    log_title:    "IML",
+    logs:         &[
+      ("OK", "06/01/2025 10:41:27"),
+      ("Critical", "18/12/2024 21:03:12"),
+      ("OK", "02/11/2024 08:14:51")
+    ],
+    newest_log:   "Network Adapter Link Up (Slot 0, Port 2)"
+  },
+  Vendor {
+    fixture:      Fixture::Ilo4,
+    auth:         AuthMode::Basic,
+    temperatures: &[("Inlet Ambient", "19°C")],
+    fans:         &[("Fan 1", "19%"), ("Fan 2", "19%"), ("Fan 3", "33%")],
+    capacity:     "920w",
+    consumed:     "143w",
+    health:       "OK",
+    model:        "ProLiant DL360p Gen8",
+    cpu:          ("CPU (2x)", "Intel(R) Xeon(R) CPU E5-2650 v2 @ 2.60GHz"),
+    ram:          "64 GB",
+    log_title:    "IML",
+    logs:         &[("Critical", "30/09/2024 17:56:02"), ("Warning", "11/06/2024 03:22:40")],
+    newest_log:   "Power Supply Failure (Power Supply 2)"
+  },
+  Vendor {
+    fixture:      Fixture::Idrac,
+    auth:         AuthMode::Session,
+    temperatures: &[("Inlet Ambient", "21°C"), ("Exhaust", "36°C")],
+    fans:         &[("System Board Fan1A", "6480 RPM"), ("System Board Fan2A", "6600 RPM")],
+    capacity:     "1596w",
+    consumed:     "212w",
+    health:       "OK",
+    model:        "PowerEdge R640",
+    cpu:          ("CPU (2x)", "Intel(R) Xeon(R) Gold 6130 CPU @ 2.10GHz"),
+    ram:          "192 GB",
+    log_title:    "Lifecycle Log",
+    logs:         &[
+      ("OK", "06/01/2025 16:12:03"),
+      ("Warning", "06/01/2025 15:47:55"),
+      ("OK", "30/12/2024 22:20:41")
+    ],
+    newest_log:   "The system inlet temperature is within range."
+  },
+  Vendor {
+    fixture:      Fixture::Supermicro,
+    auth:         AuthMode::Basic,
+    temperatures: &[("System", "33°C"), ("Peripheral", "41°C"), ("Inlet Ambient", "24°C")],
+    fans:         &[("FAN1", "5600 RPM"), ("FAN2", "5460 RPM")],
+    capacity:     "2400w",
+    consumed:     "318w",
+    health:       "Critical",
+    model:        "SYS-620U-TNR",
+    cpu:          ("CPU (2x)", "Intel(R) Xeon(R) Gold 6330 CPU @ 2.00GHz"),
+    ram:          "256 GB",
+    log_title:    "Event Log",
+    logs:         &[("Critical", "21/10/2024 14:02:37"), ("Warning", "21/10/2024 13:55:11")],
+    newest_log:   "[Fan] FAN3 Lower Non-recoverable - going low - Assertion"
+  }
+];
+
+#[tokio::test]
+async fn temperature_per_vendor() {
+  for vendor in &VENDORS {
+    let server = MockServer::start(vendor.fixture).await;
+    let report = temperature(&server.redfish_client(vendor.auth)).await.unwrap();
+
+    assert_eq!(labelled(field(&report, "Temperatures")), vendor.temperatures, "{:?}", vendor.fixture);
+    assert_eq!(labelled(field(&report, "Fans")), vendor.fans, "{:?}", vendor.fixture);
+  }
+}
+
+#[tokio::test]
+async fn power_per_vendor() {
+  for vendor in &VENDORS {
+    let server = MockServer::start(vendor.fixture).await;
+    let report = power(&server.redfish_client(vendor.auth)).await.unwrap();
+    let description = report.description.unwrap();
+
+    assert_eq!(value(&description, "Power Capacity"), vendor.capacity, "{:?}", vendor.fixture);
+    assert_eq!(value(&description, "Power Consumed"), vendor.consumed, "{:?}", vendor.fixture);
+  }
+}
+
+#[tokio::test]
+async fn system_per_vendor() {
+  for vendor in &VENDORS {
+    let server = MockServer::start(vendor.fixture).await;
+    let report = system(&server.redfish_client(vendor.auth)).await.unwrap();
+    let description = report.description.as_deref().unwrap();
+
+    assert_eq!(value(description, "Health"), vendor.health, "{:?}", vendor.fixture);
+    assert_eq!(value(description, "POST"), "Finished POST", "{:?}", vendor.fixture);
+    assert_eq!(value(description, "Model"), vendor.model, "{:?}", vendor.fixture);
+    assert_eq!(field(&report, vendor.cpu.0), vendor.cpu.1, "{:?}", vendor.fixture);
+    assert_eq!(field(&report, "RAM"), vendor.ram, "{:?}", vendor.fixture);
+  }
+}
+
+#[tokio::test]
+async fn logs_per_vendor() {
+  for vendor in &VENDORS {
+    let server = MockServer::start(vendor.fixture).await;
+    let report = logs(&server.redfish_client(vendor.auth)).await.unwrap();
+    let description = report.description.unwrap();
+    let lines = log_lines(&description);
+
+    assert_eq!(report.title, vendor.log_title, "{:?}", vendor.fixture);
+    assert_eq!(
+      lines.iter().map(|(severity, time, _)| (*severity, *time)).collect::<Vec<_>>(),
+      vendor.logs,
+      "{:?}",
+      vendor.fixture
+    );
+    assert_eq!(lines[0].2, vendor.newest_log, "{:?}", vendor.fixture);
+  }
+}
+
+#[tokio::test]
+async fn logs_longer_than_the_page_cap() {
+  let log = LongLog {
+    entries:      2000,
+    paging_query: true
+  };
+  let server = MockServer::start_with_log(Fixture::Ilo5, log).await;
+  let client = server.redfish_client(AuthMode::Basic);
+
+  let start = server.requests();
+  client.event_log().await.unwrap();
+  let lookup = server.requests() - start;
+
+  let before = server.requests();
+  let report = logs(&client).await.unwrap();
+
+  let description = report.description.unwrap();
+  let messages: Vec<&str> = log_lines(&description).into_iter().map(|(.., message)| message).collect();
+  assert_eq!(messages, (1996..=2000).rev().map(|n| format!("Generated entry {n}")).collect::<Vec<_>>());
+  // Finding the log, then its first page and the last five entries rather than 200 pages
+  assert!(server.requests() - before <= lookup + 2);
+}
+
+#[tokio::test]
+async fn through_the_shared_http_client() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = RedfishClient::new(
+    RedfishTransport::new(HttpProfile::InsecureTls),
+    &server.url(),
+    Credentials::new(USER, PASSWORD),
+    AuthMode::Session
+  );
+
+  let report = temperature(&client).await.unwrap();
+
+  assert_eq!(labelled(field(&report, "Fans")), VENDORS[0].fans);
+  assert_eq!(server.logins(), 1);
+}
+
+#[tokio::test]
+async fn error_body_through_the_shared_http_client() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = RedfishClient::new(
+    RedfishTransport::new(HttpProfile::InsecureTls),
+    &server.url(),
+    Credentials::new(USER, PASSWORD),
+    AuthMode::Basic
+  );
+  server.fail_with(StatusCode::INTERNAL_SERVER_ERROR, "The BMC is out of resources.");
+
+  // Retried like any 5xx, then handed over with its body
+  let Err(error) = temperature(&client).await else {
+    panic!("The failing BMC gave a report");
+  };
+  assert!(
+    matches!(&error, RedfishError::Status { message: Some(message), .. } if message == "The BMC is out of resources."),
+    "{error}"
+  );
+}
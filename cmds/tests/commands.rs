@@ -0,0 +1,20 @@
+use kon_cmds::{
+  canonical_name,
+  register_cmds
+};
+
+#[test]
+fn ilo_is_named_as_invoked_but_counts_as_bmc() {
+  let commands = register_cmds();
+  let ilo = commands.iter().find(|c| c.name == "ilo").expect("/ilo isn't registered");
+
+  assert_eq!(ilo.qualified_name, "ilo");
+  for subcommand in &ilo.subcommands {
+    assert_eq!(subcommand.qualified_name, format!("ilo {}", subcommand.name));
+  }
+
+  assert_eq!(canonical_name("ilo"), "bmc");
+  assert_eq!(canonical_name("ilo temperature"), "bmc temperature");
+  assert_eq!(canonical_name("bmc logs"), "bmc logs");
+  assert_eq!(canonical_name("status"), "status");
+}
@@ -0,0 +1,124 @@
+// Publishes admin commands to the running bot(s) over the Redis control channel
+
+use {
+  clap::{
+    Parser,
+    Subcommand
+  },
+  kon_libs::{
+    BINARY_PROPERTIES,
+    set_config_path
+  },
+  kon_repo::{
+    ControlCommand,
+    RedisController,
+    publish_control
+  },
+  kon_tokens::{
+    SECRETS_KEY_VAR,
+    SecretsResult,
+    init_secrets,
+    parse_key,
+    seal
+  },
+  std::{
+    env::var,
+    fs::{
+      read,
+      write
+    },
+    path::{
+      Path,
+      PathBuf
+    },
+    process::exit
+  },
+  tokio::time::{
+    Duration,
+    timeout
+  }
+};
+
+/// Connecting keeps retrying in the background, give up on it after this
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Parser, Debug)]
+#[command(name = "konctl", version, about = "Sends admin commands to the running bot(s) over Redis")]
+struct Cli {
+  /// Service name to fetch the secrets for, overrides `secrets.service` in the config
+  #[arg(short, long, env = "KON_SERVICE", global = true)]
+  service: Option<String>,
+
+  /// Config file to load
+  #[arg(short, long, env = "KON_CONFIG", default_value = kon_libs::DEFAULT_CONFIG_PATH, global = true)]
+  config: PathBuf,
+
+  #[command(subcommand)]
+  action: Action
+}
+
+#[derive(Subcommand, Debug)]
+enum Action {
+  /// Poll a feed straight away
+  Poll { feed: String },
+  /// Forget what was last posted for a feed
+  ClearCache { feed: String },
+  /// Encrypt a plain secrets file for the `file` provider with the key in `KON_SECRETS_KEY`
+  SealSecrets { input: PathBuf, output: PathBuf }
+}
+
+fn seal_secrets(
+  input: &Path,
+  output: &Path
+) -> SecretsResult<()> {
+  let key = var(SECRETS_KEY_VAR).map_err(|_| format!("{SECRETS_KEY_VAR} is not set"))?;
+  let sealed = seal(&read(input)?, &parse_key(&key)?)?;
+  write(output, sealed)?;
+  Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+  let cli = Cli::parse();
+
+  let command = match cli.action {
+    Action::Poll { feed } => ControlCommand::PollFeed(feed),
+    Action::ClearCache { feed } => ControlCommand::ClearCache(feed),
+    Action::SealSecrets { input, output } => {
+      if let Err(e) = seal_secrets(&input, &output) {
+        eprintln!("Failed to seal \"{}\": {e}", input.display());
+        exit(1);
+      }
+      println!("Sealed \"{}\" into \"{}\"", input.display(), output.display());
+      return;
+    }
+  };
+
+  set_config_path(cli.config);
+  let mut secrets = BINARY_PROPERTIES.load().secrets.clone();
+  if let Some(service) = cli.service {
+    secrets.service = service;
+  }
+  init_secrets(&secrets);
+
+  let redis = match timeout(CONNECT_TIMEOUT, RedisController::new()).await {
+    Ok(Ok(redis)) => redis,
+    Ok(Err(e)) => {
+      eprintln!("Failed to connect to Redis: {e}");
+      exit(1);
+    },
+    Err(_) => {
+      eprintln!("Couldn't reach Redis within {}s", CONNECT_TIMEOUT.as_secs());
+      exit(1);
+    }
+  };
+
+  match publish_control(&redis, &command).await {
+    Ok(0) => println!("Sent \"{command}\" but no bot instance is listening"),
+    Ok(receivers) => println!("Sent \"{command}\" to {receivers} instance(s)"),
+    Err(e) => {
+      eprintln!("Failed to publish \"{command}\": {e}");
+      exit(1);
+    }
+  }
+}
@@ -1,28 +1,180 @@
-use std::sync::LazyLock;
+mod ilo;
+pub use ilo::{
+  IloConfig,
+  IloHost
+};
 
+use {
+  crate::{
+    HttpConfig,
+    http::reset_clients,
+    logging::{
+      apply_log_filter,
+      validate_log_filter
+    }
+  },
+  kon_tokens::SecretsConfig,
+  serde::Deserialize,
+  std::{
+    env::var,
+    fmt,
+    fs::{
+      metadata,
+      read_to_string
+    },
+    io,
+    mem,
+    net::SocketAddr,
+    path::{
+      Path,
+      PathBuf
+    },
+    str::FromStr,
+    sync::{
+      Arc,
+      LazyLock,
+      OnceLock,
+      RwLock,
+      atomic::{
+        AtomicBool,
+        Ordering
+      }
+    },
+    time::SystemTime
+  },
+  tokio::time::{
+    Duration,
+    interval
+  },
+  tracing::{
+    error,
+    info,
+    warn
+  }
+};
+
+/// Where the config file is read from when neither `--config` nor `KON_CONFIG` is given
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// Runtime configuration, see `config.example.toml` for the documented schema.
+///
+/// Every key can be overridden with a `KON_`-prefixed environment variable,
+/// e.g. `KON_RSS_CHANNEL` or `KON_DEVELOPERS=1,2,3`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ConfigMeta {
   pub env:          String,
   pub embed_color:  i32,
   pub ready_notify: u64,
   pub rss_channel:  u64,
   pub kon_logs:     u64,
-  pub developers:   Vec<u64>
+  pub developers:   Vec<u64>,
+  /// Seconds between RSS feed checks
+  pub rss_interval: u64,
+  /// Log filter directives, e.g. `info,kon_tasks=debug`
+  pub log_filter:   Option<String>,
+  /// Address for the metrics and health endpoints, the server stays off if unset (only read on startup)
+  pub server_addr:  Option<SocketAddr>,
+  /// Outgoing HTTP requests
+  pub http:         HttpConfig,
+  /// Servers for `/bmc`, the section can also be called `[bmc]`
+  #[serde(alias = "bmc")]
+  pub ilo:          IloConfig,
+  /// Where the bot's credentials come from, only read on startup
+  pub secrets:      SecretsConfig
 }
 
-#[cfg(feature = "production")]
-pub static BINARY_PROPERTIES: LazyLock<ConfigMeta> = LazyLock::new(ConfigMeta::new);
+/// How often the config file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
 
-#[cfg(not(feature = "production"))]
-pub static BINARY_PROPERTIES: LazyLock<ConfigMeta> = LazyLock::new(|| {
-  ConfigMeta::new()
-    .env("dev")
-    .embed_color(0xF1D63C)
-    .ready_notify(1311282815601741844)
-    .rss_channel(1311282815601741844)
+pub static BINARY_PROPERTIES: LazyLock<ConfigStore> = LazyLock::new(|| {
+  let path = config_path();
+  match ConfigMeta::load(&path) {
+    Ok(config) => ConfigStore {
+      path,
+      current: RwLock::new(Arc::new(config))
+    },
+    Err(e) => panic!("Config[Error]: {e}")
+  }
 });
 
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Holds the current config snapshot, which can be swapped out while the bot is running.
+///
+/// Always go through [`ConfigStore::load`] at the point of use instead of keeping the snapshot around,
+/// otherwise a reload won't be picked up.
+pub struct ConfigStore {
+  path:    PathBuf,
+  current: RwLock<Arc<ConfigMeta>>
+}
+
+impl ConfigStore {
+  pub fn load(&self) -> Arc<ConfigMeta> { self.current.read().unwrap().clone() }
+
+  /// Re-read the config file, the current snapshot is kept if the new one doesn't pass validation
+  /// or the file has gone missing
+  pub fn reload(&self) -> Result<Arc<ConfigMeta>, ConfigError> {
+    let config = Arc::new(ConfigMeta::read(&self.path, false)?);
+    let previous = mem::replace(&mut *self.current.write().unwrap(), config.clone());
+    apply_log_filter(config.log_filter.as_deref());
+
+    if previous.http != config.http {
+      reset_clients();
+    }
+    Ok(config)
+  }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> { metadata(path).and_then(|m| m.modified()).ok() }
+
+/// Poll the config file and reload it whenever it changes on disk
+pub async fn watch_config() {
+  if WATCHING.swap(true, Ordering::SeqCst) {
+    return;
+  }
+
+  let path = BINARY_PROPERTIES.path.clone();
+  let mut last_modified = modified_at(&path);
+  let mut interval = interval(WATCH_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    let modified = modified_at(&path);
+    if modified == last_modified {
+      continue;
+    }
+    last_modified = modified;
+
+    match BINARY_PROPERTIES.reload() {
+      Ok(_) => info!("Reloaded \"{}\"", path.display()),
+      Err(e) => error!("{e}, keeping the current config")
+    }
+  }
+}
+
+impl Default for ConfigMeta {
+  #[cfg(feature = "production")]
+  fn default() -> Self { Self::production() }
+
+  /// Development builds post to the test server unless the config file says otherwise
+  #[cfg(not(feature = "production"))]
+  fn default() -> Self {
+    Self {
+      env: "dev".to_string(),
+      embed_color: 0xF1D63C,
+      ready_notify: 1311282815601741844,
+      rss_channel: 1311282815601741844,
+      ..Self::production()
+    }
+  }
+}
+
 impl ConfigMeta {
-  fn new() -> Self {
+  fn production() -> Self {
     Self {
       env:          "prod".to_string(),
       embed_color:  0x5A99C7,
@@ -31,44 +183,200 @@ impl ConfigMeta {
       kon_logs:     1268493237912604672,
       developers:   vec![
         190407856527376384, // toast.ts
-      ]
+      ],
+      rss_interval: 300,
+      log_filter:   None,
+      server_addr:  None,
+      http:         HttpConfig::default(),
+      ilo:          IloConfig::default(),
+      secrets:      SecretsConfig::default()
     }
   }
+}
 
-  // Scalable functions below;
-  #[cfg(not(feature = "production"))]
-  fn env(
-    mut self,
-    env: &str
+#[derive(Debug)]
+pub enum ConfigError {
+  Read { path: PathBuf, source: io::Error },
+  Parse { path: PathBuf, source: toml::de::Error },
+  Invalid { key: String, reason: String }
+}
+
+impl ConfigError {
+  fn invalid(
+    key: &str,
+    reason: impl Into<String>
   ) -> Self {
-    self.env = env.to_string();
-    self
+    Self::Invalid {
+      key:    key.to_string(),
+      reason: reason.into()
+    }
   }
+}
 
-  #[cfg(not(feature = "production"))]
-  fn embed_color(
-    mut self,
-    color: i32
-  ) -> Self {
-    self.embed_color = color;
-    self
+impl fmt::Display for ConfigError {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    match self {
+      Self::Read { path, source } => write!(f, "Couldn't read \"{}\": {source}", path.display()),
+      Self::Parse { path, source } => write!(f, "Couldn't parse \"{}\": {source}", path.display()),
+      Self::Invalid { key, reason } => write!(f, "Invalid value for `{key}`: {reason}")
+    }
   }
+}
 
-  #[cfg(not(feature = "production"))]
-  fn ready_notify(
-    mut self,
-    channel_id: u64
-  ) -> Self {
-    self.ready_notify = channel_id;
-    self
+impl std::error::Error for ConfigError {}
+
+/// Point [`BINARY_PROPERTIES`] at another file, only has an effect before the config is first loaded
+pub fn set_config_path(path: PathBuf) -> bool { CONFIG_PATH.set(path).is_ok() }
+
+pub fn config_path() -> PathBuf {
+  if let Some(path) = CONFIG_PATH.get() {
+    return path.clone();
   }
 
-  #[cfg(not(feature = "production"))]
-  fn rss_channel(
-    mut self,
-    channel_id: u64
-  ) -> Self {
-    self.rss_channel = channel_id;
-    self
+  var("KON_CONFIG")
+    .map(PathBuf::from)
+    .unwrap_or_else(|_| PathBuf::from(DEFAULT_CONFIG_PATH))
+}
+
+impl ConfigMeta {
+  /// Read the config file (defaults are used if it doesn't exist), apply the environment overrides and validate the result
+  pub fn load(path: &Path) -> Result<Self, ConfigError> { Self::read(path, true) }
+
+  fn read(
+    path: &Path,
+    allow_missing: bool
+  ) -> Result<Self, ConfigError> {
+    let mut config = match read_to_string(path) {
+      Ok(content) => toml::from_str(&content).map_err(|source| ConfigError::Parse {
+        path: path.to_path_buf(),
+        source
+      })?,
+      Err(e) if allow_missing && e.kind() == io::ErrorKind::NotFound => {
+        warn!("\"{}\" not found, using defaults", path.display());
+        Self::default()
+      },
+      Err(source) => {
+        return Err(ConfigError::Read {
+          path: path.to_path_buf(),
+          source
+        });
+      }
+    };
+
+    config.apply_env()?;
+    config.validate()?;
+    Ok(config)
   }
+
+  fn apply_env(&mut self) -> Result<(), ConfigError> {
+    env_override("KON_ENV", &mut self.env)?;
+    env_override("KON_READY_NOTIFY", &mut self.ready_notify)?;
+    env_override("KON_RSS_CHANNEL", &mut self.rss_channel)?;
+    env_override("KON_KON_LOGS", &mut self.kon_logs)?;
+    env_override("KON_RSS_INTERVAL", &mut self.rss_interval)?;
+    env_override("KON_HTTP_MAX_ATTEMPTS", &mut self.http.retry.max_attempts)?;
+    env_override("KON_SECRETS_PROVIDER", &mut self.secrets.provider)?;
+    env_override("KON_SECRETS_SERVICE", &mut self.secrets.service)?;
+    env_override("KON_SECRETS_PATH", &mut self.secrets.path)?;
+    env_override("KON_SECRETS_ENCRYPTED", &mut self.secrets.encrypted)?;
+    env_override("KON_SECRETS_TTL", &mut self.secrets.ttl)?;
+
+    if let Ok(value) = var("KON_LOG_FILTER") {
+      self.log_filter = Some(value);
+    }
+
+    // Keeps proxy credentials out of the config file
+    if let Ok(value) = var("KON_HTTP_PROXY") {
+      self.http.proxy.get_or_insert_with(Default::default).url = value;
+    }
+
+    if let Ok(value) = var("KON_SERVER_ADDR") {
+      let addr: SocketAddr = value.parse().map_err(|e| ConfigError::invalid("KON_SERVER_ADDR", format!("{e}")))?;
+      self.server_addr = Some(addr);
+    }
+
+    if let Ok(value) = var("KON_EMBED_COLOR") {
+      let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix('#')) {
+        Some(hex) => i32::from_str_radix(hex, 16),
+        None => value.parse()
+      };
+      self.embed_color = parsed.map_err(|e| ConfigError::invalid("KON_EMBED_COLOR", e.to_string()))?;
+    }
+
+    if let Ok(value) = var("KON_DEVELOPERS") {
+      self.developers = value
+        .split(',')
+        .map(|id| id.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|e| ConfigError::invalid("KON_DEVELOPERS", format!("{e}, expected a comma-separated list of user ids")))?;
+    }
+
+    Ok(())
+  }
+
+  fn validate(&self) -> Result<(), ConfigError> {
+    if self.env.trim().is_empty() {
+      return Err(ConfigError::invalid("env", "must not be empty"));
+    }
+
+    if !(0..=0xFFFFFF).contains(&self.embed_color) {
+      return Err(ConfigError::invalid("embed_color", "must be a 24-bit RGB color, e.g. 0x5A99C7"));
+    }
+
+    for (key, id) in [
+      ("ready_notify", self.ready_notify),
+      ("rss_channel", self.rss_channel),
+      ("kon_logs", self.kon_logs)
+    ] {
+      if id == 0 {
+        return Err(ConfigError::invalid(key, "must be a channel id"));
+      }
+    }
+
+    if self.developers.contains(&0) {
+      return Err(ConfigError::invalid("developers", "must only contain user ids"));
+    }
+
+    if self.rss_interval < 10 {
+      return Err(ConfigError::invalid("rss_interval", "must be at least 10 seconds"));
+    }
+
+    if let Some(directives) = &self.log_filter
+      && let Err(reason) = validate_log_filter(directives)
+    {
+      return Err(ConfigError::invalid("log_filter", reason));
+    }
+
+    if let Err((key, reason)) = self.http.validate() {
+      return Err(ConfigError::invalid(&format!("http.{key}"), reason));
+    }
+
+    if let Err((key, reason)) = self.ilo.validate() {
+      return Err(ConfigError::invalid(&format!("ilo.{key}"), reason));
+    }
+
+    if let Err((key, reason)) = self.secrets.validate() {
+      return Err(ConfigError::invalid(&format!("secrets.{key}"), reason));
+    }
+
+    Ok(())
+  }
+}
+
+fn env_override<T>(
+  key: &str,
+  target: &mut T
+) -> Result<(), ConfigError>
+where
+  T: FromStr,
+  T::Err: fmt::Display
+{
+  if let Ok(value) = var(key) {
+    *target = value.parse().map_err(|e: T::Err| ConfigError::invalid(key, e.to_string()))?;
+  }
+
+  Ok(())
 }
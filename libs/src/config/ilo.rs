@@ -0,0 +1,68 @@
+use {
+  kon_redfish::AuthMode,
+  serde::Deserialize
+};
+
+/// The `[ilo]` section, servers `/bmc` can talk to
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct IloConfig {
+  /// Host used when the `server` argument is left empty, the first one if unset
+  pub default: Option<String>,
+  pub hosts:   Vec<IloHost>
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct IloHost {
+  /// Shown in embeds and picked with the `server` argument
+  pub name:        String,
+  /// IP or hostname of the BMC (iLO, iDRAC or Supermicro), or a full URL
+  pub address:     String,
+  /// Key of the login in the secrets' `ilo` table (any case), `default` is `ilo_user` and `ilo_pw`
+  #[serde(default = "default_credentials")]
+  pub credentials: String,
+  /// `basic` sends the login with every request, `session` logs in once and reuses the token
+  #[serde(default)]
+  pub auth:        AuthMode
+}
+
+fn default_credentials() -> String { "default".to_string() }
+
+impl IloConfig {
+  /// Look up a host by name (case-insensitive), or the default one
+  pub fn host(
+    &self,
+    name: Option<&str>
+  ) -> Option<&IloHost> {
+    match name.or(self.default.as_deref()) {
+      Some(name) => self.hosts.iter().find(|host| host.name.eq_ignore_ascii_case(name)),
+      None => self.hosts.first()
+    }
+  }
+
+  pub(super) fn validate(&self) -> Result<(), (String, String)> {
+    for (i, host) in self.hosts.iter().enumerate() {
+      if host.name.trim().is_empty() {
+        return Err((format!("hosts[{i}].name"), "must not be empty".to_string()));
+      }
+
+      if host.address.trim().is_empty() {
+        return Err((format!("hosts[{i}].address"), "must not be empty".to_string()));
+      }
+
+      if self.hosts[..i].iter().any(|other| other.name.eq_ignore_ascii_case(&host.name)) {
+        return Err((format!("hosts[{i}].name"), format!("\"{}\" is used more than once", host.name)));
+      }
+    }
+
+    if let Some(default) = &self.default
+      && !self.hosts.is_empty()
+      && self.host(Some(default)).is_none()
+    {
+      return Err(("default".to_string(), format!("\"{default}\" isn't one of the hosts")));
+    }
+
+    Ok(())
+  }
+}
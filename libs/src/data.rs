@@ -1 +1,71 @@
-pub struct KonData {}
+use {
+  super::{
+    KonResult,
+    metrics::track_redis_pool
+  },
+  kon_repo::{
+    GuildSettings,
+    RedisController
+  },
+  std::{
+    collections::HashMap,
+    sync::{
+      Arc,
+      RwLock
+    },
+    time::{
+      Duration,
+      Instant
+    }
+  }
+};
+
+/// How long guild settings are served from memory before going back to Redis
+const SETTINGS_TTL: Duration = Duration::from_secs(300);
+
+pub struct KonData {
+  redis:          RedisController,
+  guild_settings: RwLock<HashMap<u64, (Instant, Arc<GuildSettings>)>>
+}
+
+impl KonData {
+  pub async fn new() -> KonResult<Self> {
+    let redis = RedisController::new().await?;
+    track_redis_pool("commands", redis.clone());
+
+    Ok(Self {
+      redis,
+      guild_settings: RwLock::new(HashMap::new())
+    })
+  }
+
+  /// Settings for a guild, cached for a few minutes
+  pub async fn guild_settings(
+    &self,
+    guild_id: u64
+  ) -> KonResult<Arc<GuildSettings>> {
+    if let Some((cached_at, settings)) = self.guild_settings.read().unwrap().get(&guild_id)
+      && cached_at.elapsed() < SETTINGS_TTL
+    {
+      return Ok(settings.clone());
+    }
+
+    let settings = Arc::new(self.redis.guild_settings(guild_id).await?);
+    self.guild_settings.write().unwrap().insert(guild_id, (Instant::now(), settings.clone()));
+
+    Ok(settings)
+  }
+
+  pub async fn save_guild_settings(
+    &self,
+    guild_id: u64,
+    settings: GuildSettings
+  ) -> KonResult<Arc<GuildSettings>> {
+    self.redis.set_guild_settings(guild_id, &settings).await?;
+
+    let settings = Arc::new(settings);
+    self.guild_settings.write().unwrap().insert(guild_id, (Instant::now(), settings.clone()));
+
+    Ok(settings)
+  }
+}
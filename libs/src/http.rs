@@ -1,50 +1,169 @@
+mod error;
+mod limits;
+mod proxy;
+mod redfish;
+mod registry;
+mod request;
+mod retry;
+
+pub(crate) use registry::reset_clients;
+pub use {
+  error::HttpError,
+  limits::{
+    CircuitBreaker,
+    HostRate,
+    RateLimit
+  },
+  proxy::ProxyConfig,
+  redfish::RedfishTransport,
+  registry::{
+    HttpProfile,
+    shared_client
+  },
+  request::HttpRequest,
+  reqwest::{
+    Method,
+    StatusCode,
+    header
+  },
+  retry::RetryPolicy
+};
+
 use {
   reqwest::{
     Client,
-    Error,
-    Response
+    header::{
+      HeaderName,
+      HeaderValue
+    }
   },
-  std::time::Duration
+  serde::Deserialize,
+  std::collections::BTreeMap
 };
 
-const ERROR_PREFIX: &str = "HTTPClient[Error]:";
+/// The `[http]` section of the config
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+  pub retry:                  RetryPolicy,
+  /// Headers sent with every request, on top of the User-Agent
+  pub default_headers:        BTreeMap<String, String>,
+  pub proxy:                  Option<ProxyConfig>,
+  /// Seconds an idle pooled connection is kept around
+  pub pool_idle_secs:         u64,
+  pub pool_max_idle_per_host: usize,
+  /// Largest response body that's read unless a request sets its own limit
+  pub max_response_bytes:     usize,
+  pub rate_limit:             RateLimit,
+  pub circuit_breaker:        CircuitBreaker
+}
+
+impl Default for HttpConfig {
+  fn default() -> Self {
+    Self {
+      retry:                  RetryPolicy::default(),
+      default_headers:        BTreeMap::new(),
+      proxy:                  None,
+      pool_idle_secs:         90,
+      pool_max_idle_per_host: 8,
+      max_response_bytes:     10 * 1024 * 1024,
+      rate_limit:             RateLimit::default(),
+      circuit_breaker:        CircuitBreaker::default()
+    }
+  }
+}
+
+impl HttpConfig {
+  pub(crate) fn validate(&self) -> Result<(), (String, String)> {
+    if let Err((key, reason)) = self.retry.validate() {
+      return Err((format!("retry.{key}"), reason.to_string()));
+    }
+
+    for (name, value) in &self.default_headers {
+      if let Err(e) = HeaderName::from_bytes(name.as_bytes()) {
+        return Err(("default_headers".to_string(), format!("\"{name}\" {e}")));
+      }
+
+      if let Err(e) = HeaderValue::from_str(value) {
+        return Err(("default_headers".to_string(), format!("value of \"{name}\" {e}")));
+      }
+    }
+
+    if let Err((key, reason)) = self.rate_limit.validate() {
+      return Err((format!("rate_limit.{key}"), reason.to_string()));
+    }
+
+    if self.circuit_breaker.open_secs == 0 {
+      return Err(("circuit_breaker.open_secs".to_string(), "must be at least 1".to_string()));
+    }
+
+    if let Some(proxy) = &self.proxy
+      && let Err((key, reason)) = proxy.validate()
+    {
+      return Err((format!("proxy.{key}"), reason));
+    }
+
+    if self.max_response_bytes == 0 {
+      return Err(("max_response_bytes".to_string(), "must be larger than 0".to_string()));
+    }
+
+    Ok(())
+  }
+}
 
+/// Cheap handle to one of the shared clients, clone or construct it wherever it's needed
+#[derive(Clone)]
 pub struct HttpClient(Client);
 
 impl Default for HttpClient {
-  fn default() -> Self { Self::new() }
+  fn default() -> Self { Self::shared() }
 }
 
 impl HttpClient {
-  pub fn new() -> Self { Self(Client::new()) }
+  pub fn shared() -> Self { Self::profile(HttpProfile::Default) }
 
-  pub async fn get(
+  pub fn profile(profile: HttpProfile) -> Self { Self(shared_client(profile)) }
+
+  pub fn request(
     &self,
-    url: &str,
-    ua: &str
-  ) -> Result<Response, Error> {
-    let response = self
-      .0
-      .get(url)
-      .header(
-        reqwest::header::USER_AGENT,
-        format!("Kon ({}-{}) - {ua}/reqwest", crate::BOT_VERSION.as_str(), crate::GIT_COMMIT_HASH)
-      )
-      .timeout(Duration::from_secs(30))
-      .send()
-      .await;
-
-    match response {
-      Ok(res) => Ok(res),
-      Err(y) if y.is_timeout() => {
-        eprintln!("{ERROR_PREFIX} Request timed out for \"{url}\"");
-        Err(y)
-      },
-      Err(y) if y.is_connect() => {
-        eprintln!("{ERROR_PREFIX} Connection failed for \"{url}\"");
-        Err(y)
-      },
-      Err(y) => Err(y)
-    }
+    method: Method,
+    url: &str
+  ) -> HttpRequest {
+    HttpRequest::new(self.0.clone(), method, url)
+  }
+
+  pub fn get(
+    &self,
+    url: &str
+  ) -> HttpRequest {
+    self.request(Method::GET, url)
+  }
+
+  pub fn post(
+    &self,
+    url: &str
+  ) -> HttpRequest {
+    self.request(Method::POST, url)
+  }
+
+  pub fn put(
+    &self,
+    url: &str
+  ) -> HttpRequest {
+    self.request(Method::PUT, url)
+  }
+
+  pub fn patch(
+    &self,
+    url: &str
+  ) -> HttpRequest {
+    self.request(Method::PATCH, url)
+  }
+
+  pub fn delete(
+    &self,
+    url: &str
+  ) -> HttpRequest {
+    self.request(Method::DELETE, url)
   }
 }
@@ -0,0 +1,107 @@
+use {
+  crate::format_bytes,
+  reqwest::StatusCode,
+  std::{
+    error::Error,
+    fmt
+  },
+  tokio::time::Duration
+};
+
+/// How much of a response body ends up in a [`HttpError::Decode`]
+const SNIPPET_LEN: usize = 200;
+
+#[derive(Debug)]
+pub enum HttpError {
+  /// No response came back, the last attempt's error is kept
+  Request {
+    url:      String,
+    attempts: u32,
+    source:   reqwest::Error
+  },
+  /// The server answered with an error status, after retrying if it was a 5xx or 429
+  Status { url: String, attempts: u32, status: StatusCode },
+  /// The URL can't be parsed or has no host, nothing was sent
+  InvalidUrl { url: String, reason: String },
+  /// The host failed too often recently, nothing was sent
+  CircuitOpen { host: String, retry_in: Duration },
+  /// The body is over the request's size limit
+  TooLarge { url: String, limit: usize },
+  /// The request body couldn't be serialized, nothing was sent
+  Encode { url: String, source: serde_json::Error },
+  /// The response body isn't the JSON that was expected
+  Decode {
+    url:     String,
+    source:  serde_json::Error,
+    snippet: String
+  }
+}
+
+impl HttpError {
+  /// Attempts made before giving up, if it got that far
+  pub fn attempts(&self) -> Option<u32> {
+    match self {
+      Self::Request { attempts, .. } | Self::Status { attempts, .. } => Some(*attempts),
+      _ => None
+    }
+  }
+
+  pub fn status(&self) -> Option<StatusCode> {
+    match self {
+      Self::Request { source, .. } => source.status(),
+      Self::Status { status, .. } => Some(*status),
+      _ => None
+    }
+  }
+
+  pub fn is_timeout(&self) -> bool { matches!(self, Self::Request { source, .. } if source.is_timeout()) }
+
+  pub(super) fn decode(
+    url: &str,
+    source: serde_json::Error,
+    body: &[u8]
+  ) -> Self {
+    let body = String::from_utf8_lossy(body);
+    let mut snippet: String = body.trim().chars().take(SNIPPET_LEN).collect();
+    if body.trim().chars().count() > SNIPPET_LEN {
+      snippet.push('…');
+    }
+
+    Self::Decode {
+      url: url.to_string(),
+      source,
+      snippet
+    }
+  }
+}
+
+impl fmt::Display for HttpError {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    match self {
+      Self::Request { url, attempts, source } => write!(f, "Request to \"{url}\" failed after {attempts} attempt(s): {source}"),
+      Self::Status { url, attempts, status } => write!(f, "\"{url}\" answered with {status} after {attempts} attempt(s)"),
+      Self::InvalidUrl { url, reason } => write!(f, "\"{url}\" isn't a usable URL: {reason}"),
+      Self::CircuitOpen { host, retry_in } => write!(
+        f,
+        "Not contacting {host} for another {}s after repeated failures",
+        retry_in.as_secs().max(1)
+      ),
+      Self::TooLarge { url, limit } => write!(f, "Response from \"{url}\" is larger than {}", format_bytes(*limit as u64)),
+      Self::Encode { url, source } => write!(f, "Couldn't encode the request body for \"{url}\": {source}"),
+      Self::Decode { url, source, snippet } => write!(f, "Couldn't decode the response from \"{url}\": {source}, body: {snippet}")
+    }
+  }
+}
+
+impl Error for HttpError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::Request { source, .. } => Some(source),
+      Self::Encode { source, .. } | Self::Decode { source, .. } => Some(source),
+      Self::Status { .. } | Self::InvalidUrl { .. } | Self::CircuitOpen { .. } | Self::TooLarge { .. } => None
+    }
+  }
+}
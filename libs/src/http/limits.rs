@@ -0,0 +1,402 @@
+use {
+  crate::{
+    BINARY_PROPERTIES,
+    metrics::HTTP_CIRCUIT_OPEN
+  },
+  reqwest::StatusCode,
+  serde::Deserialize,
+  std::{
+    collections::{
+      BTreeMap,
+      HashMap
+    },
+    sync::{
+      LazyLock,
+      Mutex
+    }
+  },
+  tokio::time::{
+    Duration,
+    Instant,
+    sleep
+  },
+  tracing::{
+    info,
+    warn
+  }
+};
+
+static HOSTS: LazyLock<Mutex<HashMap<String, Host>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Token bucket applied to every host separately
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimit {
+  /// Requests per second refilled into a host's bucket, 0 turns limiting off
+  pub per_sec: f64,
+  /// Requests that can go out back to back before the rate kicks in
+  pub burst:   u32,
+  /// Different limits for single hosts
+  pub hosts:   BTreeMap<String, HostRate>
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HostRate {
+  pub per_sec: f64,
+  pub burst:   u32
+}
+
+impl Default for RateLimit {
+  fn default() -> Self {
+    Self {
+      per_sec: 5.0,
+      burst:   10,
+      hosts:   BTreeMap::new()
+    }
+  }
+}
+
+impl RateLimit {
+  pub(super) fn validate(&self) -> Result<(), (String, &'static str)> {
+    let limits = [(String::new(), self.per_sec, self.burst)].into_iter().chain(
+      self
+        .hosts
+        .iter()
+        .map(|(host, rate)| (format!("hosts.\"{host}\"."), rate.per_sec, rate.burst))
+    );
+
+    for (prefix, per_sec, burst) in limits {
+      if !per_sec.is_finite() || per_sec < 0.0 {
+        return Err((format!("{prefix}per_sec"), "must be 0 or a positive number"));
+      }
+
+      if burst == 0 {
+        return Err((format!("{prefix}burst"), "must be at least 1"));
+      }
+    }
+
+    Ok(())
+  }
+
+  fn for_host(
+    &self,
+    host: &str
+  ) -> (f64, u32) {
+    match self.hosts.get(host) {
+      Some(rate) => (rate.per_sec, rate.burst),
+      None => (self.per_sec, self.burst)
+    }
+  }
+}
+
+/// When a host's circuit opens and for how long
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreaker {
+  /// Consecutive failed attempts (connection errors and 5xx) that open the circuit, 0 turns it off.
+  /// Every retry is an attempt of its own, so a single request can account for several of them.
+  pub failures:  u32,
+  /// Seconds requests are short-circuited before a single probe is let through
+  pub open_secs: u64
+}
+
+impl Default for CircuitBreaker {
+  fn default() -> Self { Self { failures: 5, open_secs: 60 } }
+}
+
+enum Circuit {
+  Closed {
+    failures: u32
+  },
+  Open {
+    until: Instant
+  },
+  /// A probe went out, everything else waits for its outcome
+  HalfOpen {
+    probing_since: Instant
+  }
+}
+
+struct Host {
+  tokens:   f64,
+  refilled: Instant,
+  circuit:  Circuit
+}
+
+impl Host {
+  fn new(burst: u32) -> Self {
+    Self {
+      tokens:   burst as f64,
+      refilled: Instant::now(),
+      circuit:  Circuit::Closed { failures: 0 }
+    }
+  }
+
+  /// Time until the circuit lets anything through again
+  fn blocked_for(
+    &self,
+    now: Instant,
+    open: Duration
+  ) -> Option<Duration> {
+    match self.circuit {
+      Circuit::Open { until } if now < until => Some(until - now),
+      // A probe that never reported back (dropped future) doesn't block the host forever
+      Circuit::HalfOpen { probing_since } if now < probing_since + open => Some(probing_since + open - now),
+      _ => None
+    }
+  }
+
+  /// Take a token or return how long until the next one is refilled
+  fn take_token(
+    &mut self,
+    now: Instant,
+    (per_sec, burst): (f64, u32)
+  ) -> Result<(), Duration> {
+    if per_sec == 0.0 {
+      return Ok(());
+    }
+
+    let elapsed = now.duration_since(self.refilled).as_secs_f64();
+    self.tokens = (self.tokens + elapsed * per_sec).min(burst as f64);
+    self.refilled = now;
+
+    if self.tokens >= 1.0 {
+      self.tokens -= 1.0;
+      Ok(())
+    } else {
+      Err(Duration::from_secs_f64((1.0 - self.tokens) / per_sec))
+    }
+  }
+
+  fn admit(
+    &mut self,
+    now: Instant,
+    rate: (f64, u32),
+    breaker: &CircuitBreaker
+  ) -> Admission {
+    if breaker.failures > 0
+      && let Some(retry_in) = self.blocked_for(now, Duration::from_secs(breaker.open_secs))
+    {
+      return Admission::Blocked(retry_in);
+    }
+
+    match self.take_token(now, rate) {
+      Ok(()) => {
+        if let Circuit::Open { .. } | Circuit::HalfOpen { .. } = self.circuit {
+          self.circuit = Circuit::HalfOpen { probing_since: now };
+        }
+        Admission::Go
+      },
+      Err(wait) => Admission::Wait(wait)
+    }
+  }
+
+  fn record(
+    &mut self,
+    host: &str,
+    failed: bool,
+    now: Instant,
+    breaker: &CircuitBreaker
+  ) {
+    let open = || Circuit::Open {
+      until: now + Duration::from_secs(breaker.open_secs)
+    };
+
+    self.circuit = match (&self.circuit, failed) {
+      (Circuit::Closed { .. }, false) => Circuit::Closed { failures: 0 },
+      (Circuit::Closed { failures }, true) if failures + 1 >= breaker.failures => {
+        warn!(
+          "{host} failed {} times in a row, holding off requests for {}s",
+          failures + 1,
+          breaker.open_secs
+        );
+        HTTP_CIRCUIT_OPEN.with_label_values(&[host]).set(1);
+        open()
+      },
+      (Circuit::Closed { failures }, true) => Circuit::Closed { failures: failures + 1 },
+      (Circuit::HalfOpen { .. }, false) => {
+        info!("{host} is reachable again");
+        HTTP_CIRCUIT_OPEN.with_label_values(&[host]).set(0);
+        Circuit::Closed { failures: 0 }
+      },
+      (Circuit::HalfOpen { .. }, true) => {
+        warn!("Probe to {host} failed, holding off requests for another {}s", breaker.open_secs);
+        open()
+      },
+      // Requests let through before the circuit opened don't change anything
+      (Circuit::Open { until }, _) => Circuit::Open { until: *until }
+    };
+  }
+}
+
+enum Admission {
+  Go,
+  /// Out of tokens until then
+  Wait(Duration),
+  /// The circuit is open for that long
+  Blocked(Duration)
+}
+
+/// Wait for the host's rate limit and check its circuit, `Err` is the time until the circuit lets requests through again
+pub(super) async fn admit(host: &str) -> Result<(), Duration> {
+  loop {
+    let wait = {
+      let config = BINARY_PROPERTIES.load();
+      let rate = &config.http.rate_limit;
+
+      let mut hosts = HOSTS.lock().unwrap();
+      let state = hosts.entry(host.to_string()).or_insert_with(|| Host::new(rate.for_host(host).1));
+
+      match state.admit(Instant::now(), rate.for_host(host), &config.http.circuit_breaker) {
+        Admission::Go => return Ok(()),
+        Admission::Wait(wait) => wait,
+        Admission::Blocked(retry_in) => return Err(retry_in)
+      }
+    };
+
+    sleep(wait).await;
+  }
+}
+
+/// Feed an attempt's outcome into the host's circuit, retries are fed in one by one
+pub(super) fn record(
+  host: &str,
+  failed: bool
+) {
+  let config = BINARY_PROPERTIES.load();
+  let breaker = &config.http.circuit_breaker;
+  if breaker.failures == 0 {
+    return;
+  }
+
+  if let Some(state) = HOSTS.lock().unwrap().get_mut(host) {
+    state.record(host, failed, Instant::now(), breaker);
+  }
+}
+
+/// Whether a response counts as a failed attempt, `None` for a 429 since being rate limited says nothing about the host's health
+pub(super) fn status_failed(status: StatusCode) -> Option<bool> {
+  match status {
+    StatusCode::TOO_MANY_REQUESTS => None,
+    status => Some(status.is_server_error())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const HOST: &str = "bmc.test";
+  const RATE: (f64, u32) = (2.0, 3);
+  const NO_RATE: (f64, u32) = (0.0, 1);
+
+  fn breaker() -> CircuitBreaker { CircuitBreaker { failures: 3, open_secs: 60 } }
+
+  fn secs(secs: f64) -> Duration { Duration::from_secs_f64(secs) }
+
+  #[test]
+  fn bucket_refills_and_caps_at_burst() {
+    let start = Instant::now();
+    let mut host = Host::new(RATE.1);
+
+    for _ in 0..3 {
+      assert_eq!(host.take_token(start, RATE), Ok(()));
+    }
+    assert_eq!(host.take_token(start, RATE), Err(secs(0.5)));
+
+    assert_eq!(host.take_token(start + secs(0.5), RATE), Ok(()));
+
+    // A long pause only fills the bucket back up to the burst
+    let later = start + secs(60.0);
+    for _ in 0..3 {
+      assert_eq!(host.take_token(later, RATE), Ok(()));
+    }
+    assert!(host.take_token(later, RATE).is_err());
+  }
+
+  #[test]
+  fn breaker_opens_after_failures() {
+    let now = Instant::now();
+    let mut host = Host::new(1);
+
+    for _ in 0..2 {
+      host.record(HOST, true, now, &breaker());
+      assert!(matches!(host.admit(now, NO_RATE, &breaker()), Admission::Go));
+    }
+
+    host.record(HOST, true, now, &breaker());
+    assert!(matches!(host.admit(now, NO_RATE, &breaker()), Admission::Blocked(retry_in) if retry_in == secs(60.0)));
+  }
+
+  #[test]
+  fn success_resets_the_failure_count() {
+    let now = Instant::now();
+    let mut host = Host::new(1);
+
+    host.record(HOST, true, now, &breaker());
+    host.record(HOST, true, now, &breaker());
+    host.record(HOST, false, now, &breaker());
+    host.record(HOST, true, now, &breaker());
+
+    assert!(matches!(host.admit(now, NO_RATE, &breaker()), Admission::Go));
+  }
+
+  #[test]
+  fn one_probe_while_half_open() {
+    let now = Instant::now();
+    let mut host = Host::new(1);
+    host.circuit = Circuit::Open { until: now };
+
+    assert!(matches!(host.admit(now, NO_RATE, &breaker()), Admission::Go));
+    assert!(matches!(host.admit(now + secs(1.0), NO_RATE, &breaker()), Admission::Blocked(_)));
+
+    host.record(HOST, false, now + secs(1.0), &breaker());
+    assert!(matches!(host.admit(now + secs(1.0), NO_RATE, &breaker()), Admission::Go));
+    assert!(matches!(host.circuit, Circuit::Closed { failures: 0 }));
+  }
+
+  #[test]
+  fn failed_probe_opens_the_circuit_again() {
+    let now = Instant::now();
+    let mut host = Host::new(1);
+    host.circuit = Circuit::Open { until: now };
+
+    host.admit(now, NO_RATE, &breaker());
+    host.record(HOST, true, now, &breaker());
+
+    assert!(matches!(host.admit(now + secs(59.0), NO_RATE, &breaker()), Admission::Blocked(_)));
+    assert!(matches!(host.admit(now + secs(60.0), NO_RATE, &breaker()), Admission::Go));
+  }
+
+  #[test]
+  fn dropped_probe_does_not_block_forever() {
+    let now = Instant::now();
+    let mut host = Host::new(1);
+    host.circuit = Circuit::Open { until: now };
+
+    // The probe goes out and never reports back
+    assert!(matches!(host.admit(now, NO_RATE, &breaker()), Admission::Go));
+    assert!(matches!(host.admit(now + secs(59.0), NO_RATE, &breaker()), Admission::Blocked(_)));
+
+    assert!(matches!(host.admit(now + secs(60.0), NO_RATE, &breaker()), Admission::Go));
+  }
+
+  #[test]
+  fn rate_limited_responses_are_not_failures() {
+    assert_eq!(status_failed(StatusCode::TOO_MANY_REQUESTS), None);
+    assert_eq!(status_failed(StatusCode::SERVICE_UNAVAILABLE), Some(true));
+    assert_eq!(status_failed(StatusCode::NOT_FOUND), Some(false));
+    assert_eq!(status_failed(StatusCode::OK), Some(false));
+  }
+
+  #[test]
+  fn disabled_breaker_never_blocks() {
+    let now = Instant::now();
+    let off = CircuitBreaker { failures: 0, ..breaker() };
+    let mut host = Host::new(1);
+    host.circuit = Circuit::Open { until: now + secs(60.0) };
+
+    assert!(matches!(host.admit(now, NO_RATE, &off), Admission::Go));
+  }
+}
@@ -0,0 +1,47 @@
+use {
+  super::{
+    HttpClient,
+    HttpProfile
+  },
+  async_trait::async_trait,
+  kon_redfish::{
+    RedfishError,
+    RedfishResult,
+    Request,
+    Response,
+    Transport
+  }
+};
+
+/// Sends Redfish requests through a profile's shared client with the usual retries, limits and metrics.
+///
+/// The client is looked up on every request, so a [`RedfishClient`](kon_redfish::RedfishClient) that's kept around
+/// still picks up a changed `[http]` section.
+pub struct RedfishTransport(HttpProfile);
+
+impl RedfishTransport {
+  pub fn new(profile: HttpProfile) -> Self { Self(profile) }
+}
+
+#[async_trait]
+impl Transport for RedfishTransport {
+  async fn send(
+    &self,
+    request: Request
+  ) -> RedfishResult<Response> {
+    let mut req = HttpClient::profile(self.0).request(request.method, &request.url);
+    for (name, value) in &request.headers {
+      req = req.header(name.clone(), value.clone());
+    }
+    if let Some(credentials) = &request.basic_auth {
+      req = req.basic_auth(&credentials.user, Some(&credentials.password));
+    }
+    if let Some(body) = &request.body {
+      req = req.json_body(body);
+    }
+
+    // Error statuses come back as responses too, the client reads the Redfish error from the body
+    let (status, headers, body) = req.buffered().await.map_err(|e| RedfishError::http(&request.url, e))?;
+    Ok(Response { status, headers, body })
+  }
+}
@@ -0,0 +1,115 @@
+use {
+  super::HttpConfig,
+  crate::BINARY_PROPERTIES,
+  reqwest::{
+    Client,
+    ClientBuilder,
+    header::{
+      HeaderMap,
+      HeaderName,
+      HeaderValue,
+      USER_AGENT
+    }
+  },
+  serde::Deserialize,
+  std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+      LazyLock,
+      RwLock
+    }
+  },
+  tokio::time::Duration,
+  tracing::debug
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Clients are built once per profile and shared, so connections get reused across callers
+static CLIENTS: LazyLock<RwLock<HashMap<HttpProfile, Client>>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum HttpProfile {
+  Default,
+  /// Accepts self-signed certificates, for management interfaces like iLO
+  InsecureTls,
+  /// Always goes through `http.proxy`, same as the default profile if that's unset
+  Proxy
+}
+
+impl fmt::Display for HttpProfile {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    match self {
+      Self::Default => write!(f, "default"),
+      Self::InsecureTls => write!(f, "insecure-tls"),
+      Self::Proxy => write!(f, "proxy")
+    }
+  }
+}
+
+/// The shared `reqwest` client for a profile
+pub fn shared_client(profile: HttpProfile) -> Client {
+  if let Some(client) = CLIENTS.read().unwrap().get(&profile) {
+    return client.clone();
+  }
+
+  CLIENTS
+    .write()
+    .unwrap()
+    .entry(profile)
+    .or_insert_with(|| build(profile, &BINARY_PROPERTIES.load().http))
+    .clone()
+}
+
+/// Drop the built clients so the next request picks up a changed `[http]` section
+pub(crate) fn reset_clients() { CLIENTS.write().unwrap().clear(); }
+
+fn build(
+  profile: HttpProfile,
+  config: &HttpConfig
+) -> Client {
+  debug!("Building the {profile} HTTP client");
+
+  let mut builder = ClientBuilder::new()
+    .default_headers(default_headers(config))
+    .timeout(REQUEST_TIMEOUT)
+    .connect_timeout(CONNECT_TIMEOUT)
+    .pool_idle_timeout(Duration::from_secs(config.pool_idle_secs))
+    .pool_max_idle_per_host(config.pool_max_idle_per_host);
+
+  if profile == HttpProfile::InsecureTls {
+    builder = builder.danger_accept_invalid_certs(true);
+  }
+
+  if let Some(proxy) = &config.proxy
+    && proxy.applies_to(profile)
+  {
+    debug!("The {profile} HTTP client goes through {}", proxy.endpoint());
+    // Already checked when the config was loaded
+    builder = builder.proxy(proxy.build().unwrap());
+  }
+
+  builder.build().expect("TLS backend should initialize")
+}
+
+fn default_headers(config: &HttpConfig) -> HeaderMap {
+  let mut headers = HeaderMap::new();
+  headers.insert(
+    USER_AGENT,
+    HeaderValue::from_str(&format!("Kon ({}-{})/reqwest", crate::BOT_VERSION.as_str(), crate::GIT_COMMIT_HASH)).unwrap()
+  );
+
+  for (name, value) in &config.default_headers {
+    if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+      headers.insert(name, value);
+    }
+  }
+
+  headers
+}
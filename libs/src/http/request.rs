@@ -0,0 +1,332 @@
+use {
+  super::{
+    HttpError,
+    limits::{
+      admit,
+      record,
+      status_failed
+    },
+    retry::{
+      is_idempotent,
+      retry_after,
+      should_retry
+    }
+  },
+  crate::{
+    BINARY_PROPERTIES,
+    metrics::{
+      HTTP_REQUESTS,
+      http_outcome
+    }
+  },
+  reqwest::{
+    Client,
+    Method,
+    Response,
+    StatusCode,
+    header::{
+      CONTENT_TYPE,
+      HeaderMap,
+      HeaderName,
+      HeaderValue,
+      USER_AGENT
+    }
+  },
+  serde::{
+    Serialize,
+    de::DeserializeOwned
+  },
+  std::fmt::Display,
+  tokio::time::{
+    Duration,
+    sleep
+  },
+  tracing::warn
+};
+
+enum Auth {
+  Basic { user: String, password: Option<String> },
+  Bearer(String)
+}
+
+/// A request being put together, nothing is sent until [`send`](Self::send) or one of the body readers
+#[must_use]
+pub struct HttpRequest {
+  client:  Client,
+  method:  Method,
+  url:     String,
+  headers: HeaderMap,
+  query:   Vec<(String, String)>,
+  auth:    Option<Auth>,
+  body:    Result<Option<Vec<u8>>, serde_json::Error>,
+  limit:   Option<usize>
+}
+
+impl HttpRequest {
+  pub(super) fn new(
+    client: Client,
+    method: Method,
+    url: &str
+  ) -> Self {
+    Self {
+      client,
+      method,
+      url: url.to_string(),
+      headers: HeaderMap::new(),
+      query: Vec::new(),
+      auth: None,
+      body: Ok(None),
+      limit: None
+    }
+  }
+
+  /// Name the part of the bot making the request in the User-Agent
+  pub fn user_agent(
+    self,
+    component: &str
+  ) -> Self {
+    let ua = format!("Kon ({}-{}) - {component}/reqwest", crate::BOT_VERSION.as_str(), crate::GIT_COMMIT_HASH);
+    match HeaderValue::from_str(&ua) {
+      Ok(value) => self.header(USER_AGENT, value),
+      Err(_) => self
+    }
+  }
+
+  pub fn header(
+    mut self,
+    name: HeaderName,
+    value: HeaderValue
+  ) -> Self {
+    self.headers.insert(name, value);
+    self
+  }
+
+  /// Append query parameters, they're encoded when the request is sent
+  pub fn query<K: Display, V: Display>(
+    mut self,
+    pairs: &[(K, V)]
+  ) -> Self {
+    self.query.extend(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+    self
+  }
+
+  pub fn basic_auth(
+    mut self,
+    user: &str,
+    password: Option<&str>
+  ) -> Self {
+    self.auth = Some(Auth::Basic {
+      user:     user.to_string(),
+      password: password.map(str::to_string)
+    });
+    self
+  }
+
+  pub fn bearer_auth(
+    mut self,
+    token: &str
+  ) -> Self {
+    self.auth = Some(Auth::Bearer(token.to_string()));
+    self
+  }
+
+  /// Send `body` as JSON, serializing errors are reported once the request is sent
+  pub fn json_body<T: Serialize + ?Sized>(
+    mut self,
+    body: &T
+  ) -> Self {
+    self.body = serde_json::to_vec(body).map(Some);
+    self.headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    self
+  }
+
+  /// Largest response body the readers accept, `http.max_response_bytes` if unset
+  pub fn limit(
+    mut self,
+    bytes: usize
+  ) -> Self {
+    self.limit = Some(bytes);
+    self
+  }
+
+  /// Send the request and hand back the response whatever its status.
+  ///
+  /// Idempotent methods are retried with backoff on connection errors, 5xx and 429. Once the attempts run out
+  /// (or the server asks to wait longer than `max_retry_after_secs`) the last response is returned as it is.
+  pub async fn send(self) -> Result<Response, HttpError> { self.send_counted().await.map(|(res, _)| res) }
+
+  /// Successful response body, capped at the size limit
+  pub async fn bytes(self) -> Result<Vec<u8>, HttpError> {
+    let url = self.url.clone();
+    let limit = self.limit.unwrap_or(BINARY_PROPERTIES.load().http.max_response_bytes);
+    let (res, attempts) = self.send_counted().await?;
+
+    if !res.status().is_success() {
+      return Err(HttpError::Status {
+        url,
+        attempts,
+        status: res.status()
+      });
+    }
+
+    read_limited(res, &url, attempts, limit).await
+  }
+
+  /// Status, headers and body (capped at the size limit) whatever the status, for APIs that explain their errors in the body
+  pub async fn buffered(self) -> Result<(StatusCode, HeaderMap, Vec<u8>), HttpError> {
+    let url = self.url.clone();
+    let limit = self.limit.unwrap_or(BINARY_PROPERTIES.load().http.max_response_bytes);
+    let (res, attempts) = self.send_counted().await?;
+    let (status, headers) = (res.status(), res.headers().clone());
+
+    Ok((status, headers, read_limited(res, &url, attempts, limit).await?))
+  }
+
+  pub async fn text(self) -> Result<String, HttpError> {
+    let bytes = self.bytes().await?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+  }
+
+  /// Successful response body deserialized from JSON
+  pub async fn json<T: DeserializeOwned>(self) -> Result<T, HttpError> {
+    let url = self.url.clone();
+    let bytes = self.bytes().await?;
+    serde_json::from_slice(&bytes).map_err(|e| HttpError::decode(&url, e, &bytes))
+  }
+
+  async fn send_counted(self) -> Result<(Response, u32), HttpError> {
+    let body = match self.body {
+      Ok(body) => body,
+      Err(source) => return Err(HttpError::Encode { url: self.url, source })
+    };
+
+    let policy = BINARY_PROPERTIES.load().http.retry.clone();
+    let max_attempts = if is_idempotent(&self.method) { policy.max_attempts } else { 1 };
+    let host = host_of(&self.url)?;
+    let mut attempt = 0;
+    let mut last_error = None;
+
+    loop {
+      if let Err(retry_in) = admit(&host).await {
+        // The circuit opened in between retries, what caused it is more useful than the short-circuit
+        return Err(last_error.unwrap_or(HttpError::CircuitOpen { host, retry_in }));
+      }
+      attempt += 1;
+
+      let mut req = self
+        .client
+        .request(self.method.clone(), &self.url)
+        .headers(self.headers.clone())
+        .query(&self.query);
+      req = match &self.auth {
+        Some(Auth::Basic { user, password }) => req.basic_auth(user, password.as_ref()),
+        Some(Auth::Bearer(token)) => req.bearer_auth(token),
+        None => req
+      };
+      if let Some(body) = &body {
+        req = req.body(body.clone());
+      }
+
+      let response = req.send().await;
+      HTTP_REQUESTS.with_label_values(&[host.as_str(), &http_outcome(&response)]).inc();
+      let failed = match &response {
+        Ok(res) => status_failed(res.status()),
+        Err(_) => Some(true)
+      };
+      if let Some(failed) = failed {
+        record(&host, failed);
+      }
+
+      let (error, delay) = match response {
+        Ok(res) if !should_retry(res.status()) || attempt >= max_attempts => return Ok((res, attempt)),
+        Ok(res) => {
+          let delay = match retry_after(res.headers()) {
+            Some(wait) if wait > Duration::from_secs(policy.max_retry_after_secs) => return Ok((res, attempt)),
+            Some(wait) => wait,
+            None => policy.backoff(attempt)
+          };
+
+          let error = HttpError::Status {
+            url:      self.url.clone(),
+            attempts: attempt,
+            status:   res.status()
+          };
+          (error, delay)
+        },
+        Err(source) => {
+          let delay = policy.backoff(attempt);
+          (
+            HttpError::Request {
+              url: self.url.clone(),
+              attempts: attempt,
+              source
+            },
+            delay
+          )
+        }
+      };
+
+      if attempt >= max_attempts {
+        return Err(error);
+      }
+
+      warn!("{error}, retrying in {}ms", delay.as_millis());
+      last_error = Some(error);
+      sleep(delay).await;
+    }
+  }
+}
+
+async fn read_limited(
+  mut res: Response,
+  url: &str,
+  attempts: u32,
+  limit: usize
+) -> Result<Vec<u8>, HttpError> {
+  let too_large = || HttpError::TooLarge { url: url.to_string(), limit };
+
+  if res.content_length().is_some_and(|len| len > limit as u64) {
+    return Err(too_large());
+  }
+
+  let mut body = Vec::new();
+  while let Some(chunk) = res.chunk().await.map_err(|source| HttpError::Request {
+    url: url.to_string(),
+    attempts,
+    source
+  })? {
+    if body.len() + chunk.len() > limit {
+      return Err(too_large());
+    }
+    body.extend_from_slice(&chunk);
+  }
+
+  Ok(body)
+}
+
+/// The host the limiter and circuit breaker track, a URL without one is refused before anything is sent
+fn host_of(url: &str) -> Result<String, HttpError> {
+  let invalid = |reason: String| HttpError::InvalidUrl {
+    url: url.to_string(),
+    reason
+  };
+
+  match reqwest::Url::parse(url) {
+    Ok(parsed) => parsed.host_str().map(str::to_string).ok_or_else(|| invalid("it has no host".to_string())),
+    Err(e) => Err(invalid(e.to_string()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn host_of_a_url() {
+    assert_eq!(host_of("https://bmc.test:8443/redfish/v1/").unwrap(), "bmc.test");
+
+    for url in ["", "bmc.test/redfish/v1", "data:text/plain,hello"] {
+      assert!(matches!(host_of(url), Err(HttpError::InvalidUrl { .. })), "{url}");
+    }
+  }
+}
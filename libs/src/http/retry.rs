@@ -0,0 +1,162 @@
+use {
+  chrono::{
+    DateTime,
+    Utc
+  },
+  rand::Rng,
+  reqwest::{
+    Method,
+    StatusCode,
+    header::{
+      HeaderMap,
+      RETRY_AFTER
+    }
+  },
+  serde::Deserialize,
+  tokio::time::Duration
+};
+
+/// How failed idempotent requests are retried
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryPolicy {
+  /// Attempts per request, including the first one
+  pub max_attempts:         u32,
+  /// Backoff before the first retry in milliseconds, doubles with every retry
+  pub base_delay_ms:        u64,
+  /// Upper bound for the backoff in milliseconds
+  pub max_delay_ms:         u64,
+  /// Longest `Retry-After` that gets waited out, anything longer gives up right away
+  pub max_retry_after_secs: u64
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_attempts:         3,
+      base_delay_ms:        500,
+      max_delay_ms:         10_000,
+      max_retry_after_secs: 60
+    }
+  }
+}
+
+impl RetryPolicy {
+  pub(super) fn validate(&self) -> Result<(), (&'static str, &'static str)> {
+    if self.max_attempts == 0 {
+      return Err(("max_attempts", "must be at least 1"));
+    }
+
+    if self.base_delay_ms > self.max_delay_ms {
+      return Err(("base_delay_ms", "must not be larger than max_delay_ms"));
+    }
+
+    Ok(())
+  }
+
+  /// Full jitter, anywhere between nothing and the exponential delay for this retry
+  pub(super) fn backoff(
+    &self,
+    retry: u32
+  ) -> Duration {
+    let ceiling = self
+      .base_delay_ms
+      .saturating_mul(2u64.saturating_pow(retry.saturating_sub(1)))
+      .min(self.max_delay_ms);
+    Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling))
+  }
+}
+
+pub(super) fn is_idempotent(method: &Method) -> bool {
+  matches!(
+    *method,
+    Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+  )
+}
+
+pub(super) fn should_retry(status: StatusCode) -> bool { status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS }
+
+/// `Retry-After` as either seconds or an HTTP date
+pub(super) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+  let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+  if let Ok(secs) = value.parse::<u64>() {
+    return Some(Duration::from_secs(secs));
+  }
+
+  let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+  Some((date - Utc::now()).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    reqwest::header::HeaderValue
+  };
+
+  fn policy() -> RetryPolicy {
+    RetryPolicy {
+      max_attempts:         5,
+      base_delay_ms:        100,
+      max_delay_ms:         1_000,
+      max_retry_after_secs: 60
+    }
+  }
+
+  fn headers(retry_after: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, HeaderValue::from_str(retry_after).unwrap());
+    headers
+  }
+
+  #[test]
+  fn backoff_doubles_up_to_the_cap() {
+    let policy = policy();
+
+    for (retry, ceiling) in [(1, 100), (2, 200), (3, 400), (4, 800), (5, 1_000), (40, 1_000), (u32::MAX, 1_000)] {
+      let delays: Vec<u128> = (0..200).map(|_| policy.backoff(retry).as_millis()).collect();
+      assert!(delays.iter().all(|delay| *delay <= ceiling), "retry {retry} went past {ceiling}ms");
+    }
+  }
+
+  #[test]
+  fn backoff_is_jittered() {
+    let delays: Vec<Duration> = (0..200).map(|_| policy().backoff(3)).collect();
+    assert!(delays.iter().any(|delay| *delay != delays[0]));
+
+    let no_delay = RetryPolicy {
+      base_delay_ms: 0,
+      ..policy()
+    };
+    assert_eq!(no_delay.backoff(3), Duration::ZERO);
+  }
+
+  #[test]
+  fn retry_after_in_seconds() {
+    assert_eq!(retry_after(&headers("120")), Some(Duration::from_secs(120)));
+    assert_eq!(retry_after(&headers(" 5 ")), Some(Duration::from_secs(5)));
+    assert_eq!(retry_after(&headers("0")), Some(Duration::ZERO));
+  }
+
+  #[test]
+  fn retry_after_as_a_date() {
+    let date = (Utc::now() + chrono::Duration::seconds(30))
+      .format("%a, %d %b %Y %H:%M:%S GMT")
+      .to_string();
+    let wait = retry_after(&headers(&date)).unwrap();
+    assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30), "{wait:?}");
+
+    // Already passed, no need to wait
+    assert_eq!(retry_after(&headers("Sun, 06 Nov 1994 08:49:37 GMT")), Some(Duration::ZERO));
+  }
+
+  #[test]
+  fn retry_after_missing_or_garbage() {
+    assert_eq!(retry_after(&HeaderMap::new()), None);
+
+    for value in ["soon", "-5", "1.5", "", "Someday, 32 Foo 2025 25:00:00 GMT"] {
+      assert_eq!(retry_after(&headers(value)), None, "{value}");
+    }
+  }
+}
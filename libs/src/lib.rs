@@ -1,14 +1,59 @@
 mod config;
-pub use config::BINARY_PROPERTIES;
+pub use config::{
+  BINARY_PROPERTIES,
+  ConfigError,
+  ConfigMeta,
+  ConfigStore,
+  DEFAULT_CONFIG_PATH,
+  IloConfig,
+  IloHost,
+  config_path,
+  set_config_path,
+  watch_config
+};
+
+pub mod metrics;
+
+mod log_sink;
+pub use log_sink::forward_logs;
+
+mod logging;
+pub use logging::{
+  DEFAULT_LOG_FILTER,
+  LogFormat,
+  apply_log_filter,
+  init_logging,
+  log_filter,
+  set_log_filter
+};
 
 mod types;
 pub use types::*;
 
 mod data;
-pub use data::KonData;
+pub use {
+  data::KonData,
+  kon_repo::GuildSettings
+};
 
 mod http;
-pub use http::HttpClient;
+pub use http::{
+  CircuitBreaker,
+  HostRate,
+  HttpClient,
+  HttpConfig,
+  HttpError,
+  HttpProfile,
+  HttpRequest,
+  Method,
+  ProxyConfig,
+  RateLimit,
+  RedfishTransport,
+  RetryPolicy,
+  StatusCode,
+  header,
+  shared_client
+};
 
 use {
   cargo_toml::Manifest,
@@ -33,7 +78,7 @@ pub static BOT_VERSION: LazyLock<String> = LazyLock::new(|| {
 });
 
 pub fn mention_dev(ctx: PoiseCtx<'_>) -> Option<String> {
-  let devs = BINARY_PROPERTIES.developers.clone();
+  let devs = BINARY_PROPERTIES.load().developers.clone();
   let app_owners = ctx.framework().options().owners.clone();
 
   let mut mentions = Vec::new();
@@ -47,6 +92,18 @@ pub fn mention_dev(ctx: PoiseCtx<'_>) -> Option<String> {
   if mentions.is_empty() { None } else { Some(mentions.join(", ")) }
 }
 
+/// Embed color for the guild the command was run in, falls back to the bot-wide color
+pub async fn embed_color(ctx: PoiseCtx<'_>) -> i32 {
+  if let Some(guild_id) = ctx.guild_id()
+    && let Ok(settings) = ctx.data().guild_settings(guild_id.get()).await
+    && let Some(color) = settings.embed_color
+  {
+    return color;
+  }
+
+  BINARY_PROPERTIES.load().embed_color
+}
+
 pub fn format_duration(secs: u64) -> String {
   let days = secs / 86400;
   let hours = (secs % 86400) / 3600;
@@ -0,0 +1,362 @@
+use {
+  crate::BINARY_PROPERTIES,
+  poise::serenity_prelude::{
+    ChannelId,
+    CreateMessage,
+    Http
+  },
+  std::{
+    collections::{
+      HashMap,
+      VecDeque
+    },
+    fmt,
+    sync::{
+      Arc,
+      LazyLock,
+      Mutex
+    }
+  },
+  tokio::{
+    sync::mpsc::{
+      Receiver,
+      Sender,
+      channel
+    },
+    time::{
+      Duration,
+      Instant,
+      interval
+    }
+  },
+  tracing::{
+    Event,
+    Level,
+    Subscriber,
+    field::{
+      Field,
+      Visit
+    }
+  },
+  tracing_subscriber::layer::{
+    Context,
+    Layer
+  }
+};
+
+/// Lines queued past this are dropped until the forwarder catches up
+const QUEUE_SIZE: usize = 1000;
+
+/// How often the queued lines are posted
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Identical lines within this window are only counted, not posted again
+const DEDUP_WINDOW: Duration = Duration::from_secs(600);
+
+/// At most this many messages go out per window, the rest is dropped and counted
+const RATE_LIMIT: usize = 5;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Leaves room for the code block around the lines
+const MAX_MESSAGE_LEN: usize = 1900;
+const MAX_LINE_LEN: usize = 500;
+
+/// Noisy or self-referential targets, posting their warnings could loop back into more warnings
+const IGNORED_TARGETS: &[&str] = &["serenity", "tungstenite", "hyper", "h2", "reqwest", module_path!()];
+
+/// The receiving end is taken once by [`forward_logs`]
+struct Queue {
+  tx: Sender<String>,
+  rx: Mutex<Option<Receiver<String>>>
+}
+
+static QUEUE: LazyLock<Queue> = LazyLock::new(|| {
+  let (tx, rx) = channel(QUEUE_SIZE);
+  Queue {
+    tx,
+    rx: Mutex::new(Some(rx))
+  }
+});
+
+/// Tracing layer that queues warnings and errors for [`forward_logs`]
+pub struct DiscordLogLayer;
+
+impl<S: Subscriber> Layer<S> for DiscordLogLayer {
+  fn on_event(
+    &self,
+    event: &Event<'_>,
+    _: Context<'_, S>
+  ) {
+    let meta = event.metadata();
+    if *meta.level() > Level::WARN || IGNORED_TARGETS.iter().any(|target| meta.target().starts_with(target)) {
+      return;
+    }
+
+    let mut line = LineVisitor::default();
+    event.record(&mut line);
+
+    let mut line = format!("[{} {}] {line}", meta.level(), meta.target());
+    if line.len() > MAX_LINE_LEN {
+      line.truncate(line.floor_char_boundary(MAX_LINE_LEN));
+      line.push('…');
+    }
+
+    let _ = QUEUE.tx.try_send(line);
+  }
+}
+
+#[derive(Default)]
+struct LineVisitor {
+  message: String,
+  fields:  Vec<String>
+}
+
+impl Visit for LineVisitor {
+  fn record_str(
+    &mut self,
+    field: &Field,
+    value: &str
+  ) {
+    if field.name() == "message" {
+      self.message = value.to_string();
+    } else {
+      self.fields.push(format!("{}={value}", field.name()));
+    }
+  }
+
+  fn record_debug(
+    &mut self,
+    field: &Field,
+    value: &dyn fmt::Debug
+  ) {
+    if field.name() == "message" {
+      self.message = format!("{value:?}");
+    } else {
+      self.fields.push(format!("{}={value:?}", field.name()));
+    }
+  }
+}
+
+impl fmt::Display for LineVisitor {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    for field in &self.fields {
+      write!(f, "{field} ")?;
+    }
+    write!(f, "{}", self.message)
+  }
+}
+
+struct Pending {
+  line:       String,
+  count:      usize,
+  /// Repeats that were held back by the dedup window before this one
+  suppressed: usize
+}
+
+#[derive(Default)]
+struct LogBatcher {
+  pending: Vec<Pending>,
+  /// When each line was last posted and how often it came up since
+  recent:  HashMap<String, (Instant, usize)>,
+  sent:    VecDeque<Instant>,
+  dropped: usize
+}
+
+impl LogBatcher {
+  fn push(
+    &mut self,
+    line: String
+  ) {
+    if let Some((posted, repeats)) = self.recent.get_mut(&line)
+      && posted.elapsed() < DEDUP_WINDOW
+    {
+      *repeats += 1;
+      return;
+    }
+
+    match self.pending.iter_mut().find(|p| p.line == line) {
+      Some(pending) => pending.count += 1,
+      None => {
+        let suppressed = self.recent.remove(&line).map_or(0, |(_, repeats)| repeats);
+        self.pending.push(Pending { line, count: 1, suppressed });
+      }
+    }
+  }
+
+  /// Turn the pending lines into messages, keeping within the rate limit
+  fn flush(&mut self) -> Vec<String> {
+    let now = Instant::now();
+
+    // Lines that kept repeating get one last summary once their window runs out
+    let expired: Vec<String> = self
+      .recent
+      .iter()
+      .filter(|(_, (posted, repeats))| posted.elapsed() >= DEDUP_WINDOW && *repeats > 0)
+      .map(|(line, _)| line.clone())
+      .collect();
+    for line in expired {
+      if let Some((_, repeats)) = self.recent.remove(&line) {
+        self.pending.push(Pending {
+          line,
+          count: 0,
+          suppressed: repeats
+        });
+      }
+    }
+    self.recent.retain(|_, (posted, _)| posted.elapsed() < DEDUP_WINDOW);
+
+    while self.sent.front().is_some_and(|sent| now.duration_since(*sent) >= RATE_WINDOW) {
+      self.sent.pop_front();
+    }
+
+    let mut lines = Vec::new();
+    if self.dropped > 0 && !self.pending.is_empty() {
+      lines.push(format!("{} line(s) were dropped by the rate limit", self.dropped));
+      self.dropped = 0;
+    }
+
+    for pending in self.pending.drain(..) {
+      let mut line = pending.line.clone();
+      if pending.count > 1 {
+        line.push_str(&format!(" (x{})", pending.count));
+      }
+      if pending.suppressed > 0 {
+        line.push_str(&format!(
+          " (repeated {} more time(s) in the last {}m)",
+          pending.suppressed,
+          DEDUP_WINDOW.as_secs() / 60
+        ));
+      }
+
+      if pending.count > 0 {
+        self.recent.insert(pending.line, (now, 0));
+      }
+      lines.push(line);
+    }
+
+    let mut messages: Vec<Vec<String>> = Vec::new();
+    for line in lines {
+      match messages.last_mut() {
+        Some(message) if message.iter().map(|l| l.len() + 1).sum::<usize>() + line.len() < MAX_MESSAGE_LEN => message.push(line),
+        _ => messages.push(vec![line])
+      }
+    }
+
+    let allowed = RATE_LIMIT.saturating_sub(self.sent.len());
+    if messages.len() > allowed {
+      self.dropped += messages.drain(allowed..).map(|message| message.len()).sum::<usize>();
+    }
+
+    messages
+      .into_iter()
+      .map(|message| {
+        self.sent.push_back(now);
+        format!("```\n{}\n```", message.join("\n"))
+      })
+      .collect()
+  }
+}
+
+/// Post the queued warnings and errors to the `kon_logs` channel, only the first call does anything
+pub async fn forward_logs(http: Arc<Http>) {
+  let Some(mut queue) = QUEUE.rx.lock().unwrap().take() else {
+    return;
+  };
+
+  let mut batcher = LogBatcher::default();
+  let mut interval = interval(FLUSH_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    while let Ok(line) = queue.try_recv() {
+      batcher.push(line);
+    }
+
+    for message in batcher.flush() {
+      let channel = ChannelId::new(BINARY_PROPERTIES.load().kon_logs);
+      if let Err(y) = channel.send_message(&http, CreateMessage::new().content(message)).await {
+        // Not forwarded, this module's target is ignored by the layer
+        tracing::error!("Couldn't post logs to the logs channel: {y}");
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    tokio::time::advance
+  };
+
+  fn batcher(lines: &[&str]) -> LogBatcher {
+    let mut batcher = LogBatcher::default();
+    for line in lines {
+      batcher.push(line.to_string());
+    }
+    batcher
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn lines_between_flushes_are_batched() {
+    let mut batcher = batcher(&["a", "b", "a", "a"]);
+
+    assert_eq!(batcher.flush(), ["```\na (x3)\nb\n```"]);
+    assert!(batcher.flush().is_empty());
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn long_batches_are_split() {
+    let line = "x".repeat(MAX_LINE_LEN);
+    let mut batcher = batcher(&[&format!("{line}1"), &format!("{line}2"), &format!("{line}3"), &format!("{line}4")]);
+
+    let messages = batcher.flush();
+    assert_eq!(messages.len(), 2);
+    assert!(messages.iter().all(|message| message.len() < MAX_MESSAGE_LEN + 10));
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn repeats_are_counted_until_the_window_ends() {
+    let mut batcher = batcher(&["a"]);
+    assert_eq!(batcher.flush().len(), 1);
+
+    batcher.push("a".to_string());
+    batcher.push("a".to_string());
+    assert!(batcher.flush().is_empty());
+
+    advance(DEDUP_WINDOW).await;
+    assert_eq!(batcher.flush(), ["```\na (repeated 2 more time(s) in the last 10m)\n```"]);
+    assert!(batcher.flush().is_empty());
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn repeats_are_carried_over_to_the_next_post() {
+    let mut batcher = batcher(&["a"]);
+    batcher.flush();
+    batcher.push("a".to_string());
+
+    advance(DEDUP_WINDOW).await;
+    batcher.push("a".to_string());
+    assert_eq!(batcher.flush(), ["```\na (repeated 1 more time(s) in the last 10m)\n```"]);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn messages_over_the_rate_limit_are_dropped_and_counted() {
+    let mut batcher = LogBatcher::default();
+    for i in 0..RATE_LIMIT {
+      batcher.push(i.to_string());
+      assert_eq!(batcher.flush().len(), 1);
+    }
+
+    batcher.push("over".to_string());
+    assert!(batcher.flush().is_empty());
+
+    advance(RATE_WINDOW).await;
+    batcher.push("next".to_string());
+    assert_eq!(batcher.flush(), ["```\n1 line(s) were dropped by the rate limit\nnext\n```"]);
+  }
+}
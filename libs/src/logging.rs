@@ -0,0 +1,96 @@
+use {
+  crate::log_sink::DiscordLogLayer,
+  std::{
+    env::var,
+    fmt,
+    str::FromStr,
+    sync::OnceLock
+  },
+  tracing_subscriber::{
+    EnvFilter,
+    Registry,
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt
+  }
+};
+
+/// Used when neither the config nor `RUST_LOG` sets a filter, keeps the gateway chatter out
+pub const DEFAULT_LOG_FILTER: &str = "info,serenity=warn,tungstenite=warn";
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+  /// Human-readable lines for the console
+  #[default]
+  Text,
+  /// One JSON object per line for the log shipper
+  Json
+}
+
+impl fmt::Display for LogFormat {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    match self {
+      Self::Text => write!(f, "text"),
+      Self::Json => write!(f, "json")
+    }
+  }
+}
+
+impl FromStr for LogFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_lowercase().as_str() {
+      "text" => Ok(Self::Text),
+      "json" => Ok(Self::Json),
+      other => Err(format!("unknown log format \"{other}\", expected text or json"))
+    }
+  }
+}
+
+/// Install the global subscriber, has to happen before anything logs or the events are lost.
+/// Warnings and errors are also queued up for [`forward_logs`](crate::forward_logs).
+///
+/// The filter starts out as `RUST_LOG` (or [`DEFAULT_LOG_FILTER`]) and can be swapped later with [`set_log_filter`].
+pub fn init_logging(format: LogFormat) {
+  let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+  let (filter, handle) = reload::Layer::new(filter);
+  let registry = tracing_subscriber::registry().with(filter).with(DiscordLogLayer);
+
+  let installed = match format {
+    LogFormat::Text => registry.with(tracing_subscriber::fmt::layer()).try_init(),
+    LogFormat::Json => registry.with(tracing_subscriber::fmt::layer().json().flatten_event(true)).try_init()
+  };
+
+  if installed.is_ok() {
+    let _ = FILTER.set(handle);
+  }
+}
+
+/// Swap the filter directives while running, e.g. `info,kon_tasks=debug`
+pub fn set_log_filter(directives: &str) -> Result<(), String> {
+  let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+  let handle = FILTER.get().ok_or("Logging hasn't been initialized")?;
+  handle.reload(filter).map_err(|e| e.to_string())
+}
+
+/// Directives the filter is currently using
+pub fn log_filter() -> Option<String> { FILTER.get()?.with_current(|filter| filter.to_string()).ok() }
+
+/// Apply the config's filter, falls back to `RUST_LOG` and then [`DEFAULT_LOG_FILTER`] if it doesn't set one
+pub fn apply_log_filter(configured: Option<&str>) {
+  let env = var("RUST_LOG").ok();
+  let directives = configured.or(env.as_deref()).unwrap_or(DEFAULT_LOG_FILTER);
+
+  if let Err(e) = set_log_filter(directives) {
+    tracing::error!("Couldn't apply the log filter \"{directives}\": {e}");
+  }
+}
+
+/// Check the directives without applying them
+pub(crate) fn validate_log_filter(directives: &str) -> Result<(), String> { EnvFilter::try_new(directives).map(|_| ()).map_err(|e| e.to_string()) }
@@ -0,0 +1,132 @@
+//! Prometheus metrics, served by the bot's HTTP server when `server_addr` is set
+
+use {
+  kon_repo::RedisController,
+  prometheus::{
+    Encoder,
+    GaugeVec,
+    Histogram,
+    HistogramVec,
+    IntCounterVec,
+    IntGaugeVec,
+    TextEncoder,
+    exponential_buckets,
+    gather,
+    register_gauge_vec,
+    register_histogram,
+    register_histogram_vec,
+    register_int_counter_vec,
+    register_int_gauge_vec
+  },
+  std::sync::{
+    LazyLock,
+    Mutex
+  }
+};
+
+/// Seconds, from 10ms up to about 80s
+fn duration_buckets() -> Vec<f64> { exponential_buckets(0.01, 2.0, 14).unwrap() }
+
+pub static COMMANDS_RUN: LazyLock<IntCounterVec> =
+  LazyLock::new(|| register_int_counter_vec!("kon_commands_total", "Commands run, by name", &["command"]).unwrap());
+
+pub static COMMAND_ERRORS: LazyLock<IntCounterVec> =
+  LazyLock::new(|| register_int_counter_vec!("kon_command_errors_total", "Commands that returned an error, by name", &["command"]).unwrap());
+
+pub static COMMAND_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+  register_histogram_vec!(
+    "kon_command_duration_seconds",
+    "Time from invocation until the command returned",
+    &["command"],
+    duration_buckets()
+  )
+  .unwrap()
+});
+
+pub static RSS_POLL_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+  register_histogram_vec!(
+    "kon_rss_poll_duration_seconds",
+    "Time spent polling and posting a feed",
+    &["feed", "result"],
+    duration_buckets()
+  )
+  .unwrap()
+});
+
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+  register_int_counter_vec!(
+    "kon_http_requests_total",
+    "Outgoing HTTP requests, by host and outcome (status class, timeout, connect or error)",
+    &["host", "outcome"]
+  )
+  .unwrap()
+});
+
+pub static HTTP_CIRCUIT_OPEN: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+  register_int_gauge_vec!(
+    "kon_http_circuit_open",
+    "Whether requests to a host are being short-circuited (1) or let through (0)",
+    &["host"]
+  )
+  .unwrap()
+});
+
+pub static REDIS_POOL: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+  register_int_gauge_vec!(
+    "kon_redis_pool_connections",
+    "Redis pool connections, by pool and state (idle or in_use)",
+    &["pool", "state"]
+  )
+  .unwrap()
+});
+
+pub static GATEWAY_LATENCY: LazyLock<GaugeVec> =
+  LazyLock::new(|| register_gauge_vec!("kon_gateway_latency_seconds", "Heartbeat latency per shard", &["shard"]).unwrap());
+
+pub static MIDI_CONVERSION_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
+  register_histogram!(
+    "kon_midi_conversion_duration_seconds",
+    "Time fluidsynth took to render a MIDI file",
+    duration_buckets()
+  )
+  .unwrap()
+});
+
+/// Pools sampled on every scrape
+static REDIS_POOLS: Mutex<Vec<(&'static str, RedisController)>> = Mutex::new(Vec::new());
+
+/// Report a Redis pool's usage under the given name
+pub fn track_redis_pool(
+  name: &'static str,
+  redis: RedisController
+) {
+  REDIS_POOLS.lock().unwrap().push((name, redis));
+}
+
+/// Outcome label for [`HTTP_REQUESTS`]
+pub(crate) fn http_outcome(result: &Result<reqwest::Response, reqwest::Error>) -> String {
+  match result {
+    Ok(res) => format!("{}xx", res.status().as_u16() / 100),
+    Err(y) if y.is_timeout() => "timeout".to_string(),
+    Err(y) if y.is_connect() => "connect".to_string(),
+    Err(_) => "error".to_string()
+  }
+}
+
+/// Everything in the Prometheus text format
+pub fn render() -> String {
+  for (name, redis) in REDIS_POOLS.lock().unwrap().iter() {
+    let (connections, idle) = redis.pool_state();
+    REDIS_POOL.with_label_values(&[name, "idle"]).set(idle.into());
+    REDIS_POOL
+      .with_label_values(&[name, "in_use"])
+      .set(connections.saturating_sub(idle).into());
+  }
+
+  let mut buffer = Vec::new();
+  if let Err(y) = TextEncoder::new().encode(&gather(), &mut buffer) {
+    tracing::error!("Couldn't encode the metrics: {y}");
+  }
+
+  String::from_utf8(buffer).unwrap_or_default()
+}
@@ -0,0 +1,303 @@
+//! A Redfish service that answers from recorded BMC responses, for testing the client and `/bmc` offline
+
+mod fixtures;
+mod long_log;
+
+pub use {
+  axum::http::StatusCode,
+  fixtures::Fixture,
+  long_log::LongLog
+};
+
+use {
+  axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{
+      HeaderMap,
+      Method,
+      Uri,
+      header::{
+        AUTHORIZATION,
+        CONTENT_TYPE,
+        LOCATION
+      }
+    },
+    response::{
+      IntoResponse,
+      Response
+    }
+  },
+  kon_redfish::{
+    AuthMode,
+    Credentials,
+    RedfishClient
+  },
+  serde_json::{
+    Value,
+    json
+  },
+  std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{
+      Arc,
+      Mutex,
+      atomic::{
+        AtomicU32,
+        Ordering
+      }
+    }
+  },
+  tokio::{
+    net::TcpListener,
+    spawn,
+    task::JoinHandle
+  }
+};
+
+pub const USER: &str = "kon";
+pub const PASSWORD: &str = "hunter2";
+
+/// `USER:PASSWORD` as the client sends it
+const BASIC_AUTH: &str = "Basic a29uOmh1bnRlcjI=";
+const AUTH_TOKEN: &str = "X-Auth-Token";
+const SERVICE_ROOT: &str = "/redfish/v1";
+const SESSIONS: &str = "/redfish/v1/SessionService/Sessions";
+
+struct Bmc {
+  fixture:      Fixture,
+  long_log:     Option<LongLog>,
+  /// Answer every resource with this error, see [`MockServer::fail_with`]
+  failure:      Mutex<Option<(StatusCode, &'static str)>>,
+  /// Token to session id
+  sessions:     Mutex<HashMap<String, u32>>,
+  next_session: AtomicU32,
+  logins:       AtomicU32,
+  requests:     AtomicU32
+}
+
+impl Bmc {
+  fn authorized(
+    &self,
+    headers: &HeaderMap
+  ) -> bool {
+    if headers.get(AUTHORIZATION).is_some_and(|value| value == BASIC_AUTH) {
+      return true;
+    }
+
+    headers
+      .get(AUTH_TOKEN)
+      .and_then(|value| value.to_str().ok())
+      .is_some_and(|token| self.sessions.lock().unwrap().contains_key(token))
+  }
+
+  fn login(
+    &self,
+    body: &[u8]
+  ) -> Response {
+    let body: Value = serde_json::from_slice(body).unwrap_or_default();
+    if body["UserName"] != USER || body["Password"] != PASSWORD {
+      return error(StatusCode::UNAUTHORIZED, "Invalid login attempt.");
+    }
+
+    let id = self.next_session.fetch_add(1, Ordering::SeqCst) + 1;
+    let token = format!("mock-token-{id}");
+    let location = format!("{SESSIONS}/{id}/");
+    self.sessions.lock().unwrap().insert(token.clone(), id);
+    self.logins.fetch_add(1, Ordering::SeqCst);
+
+    (
+      StatusCode::CREATED,
+      [(CONTENT_TYPE, "application/json".to_string()), (LOCATION, location.clone())],
+      [(AUTH_TOKEN, token)],
+      json!({ "@odata.id": location, "Id": id.to_string(), "UserName": USER }).to_string()
+    )
+      .into_response()
+  }
+
+  /// A session can only be ended with its own token
+  fn logout(
+    &self,
+    path: &str,
+    headers: &HeaderMap
+  ) -> Response {
+    let token = headers.get(AUTH_TOKEN).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    let mut sessions = self.sessions.lock().unwrap();
+
+    match sessions.get(token) {
+      Some(id) if path == format!("{SESSIONS}/{id}") => {
+        sessions.remove(token);
+        StatusCode::OK.into_response()
+      },
+      Some(_) => error(StatusCode::NOT_FOUND, "The session doesn't exist."),
+      None => no_session()
+    }
+  }
+}
+
+fn error(
+  status: StatusCode,
+  message: &str
+) -> Response {
+  let body = json!({
+    "error": {
+      "code": "Base.1.0.GeneralError",
+      "message": "A general error has occurred. See ExtendedInfo for more information.",
+      "@Message.ExtendedInfo": [{ "Message": message }]
+    }
+  });
+
+  (status, [(CONTENT_TYPE, "application/json")], body.to_string()).into_response()
+}
+
+fn no_session() -> Response { error(StatusCode::UNAUTHORIZED, "There is no valid session established with the implementation.") }
+
+fn resource(
+  fixture: Fixture,
+  key: &str
+) -> Response {
+  match fixture.resource(key) {
+    Some(body) => ([(CONTENT_TYPE, "application/json")], body).into_response(),
+    None => error(StatusCode::NOT_FOUND, "The resource at the URI is not found.")
+  }
+}
+
+async fn handle(
+  State(bmc): State<Arc<Bmc>>,
+  method: Method,
+  uri: Uri,
+  headers: HeaderMap,
+  body: Bytes
+) -> Response {
+  bmc.requests.fetch_add(1, Ordering::SeqCst);
+
+  let path = uri.path().trim_end_matches('/');
+  let key = match uri.query() {
+    Some(query) => format!("{path}?{query}"),
+    None => path.to_string()
+  };
+
+  if method == Method::POST && path == SESSIONS {
+    return bmc.login(&body);
+  }
+  if method == Method::DELETE && path.starts_with(SESSIONS) {
+    return bmc.logout(path, &headers);
+  }
+  if method != Method::GET {
+    return error(StatusCode::METHOD_NOT_ALLOWED, "The operation is not allowed on this resource.");
+  }
+
+  // The service root is readable without logging in, like on a real BMC
+  if path != SERVICE_ROOT && !bmc.authorized(&headers) {
+    return no_session();
+  }
+  if let Some((status, message)) = *bmc.failure.lock().unwrap() {
+    return error(status, message);
+  }
+
+  match bmc.long_log {
+    Some(log) if path == bmc.fixture.event_log_entries() => {
+      ([(CONTENT_TYPE, "application/json")], log.page(path, uri.query()).to_string()).into_response()
+    },
+    _ => resource(bmc.fixture, &key)
+  }
+}
+
+/// A running mock BMC, shut down when dropped
+pub struct MockServer {
+  address: SocketAddr,
+  bmc:     Arc<Bmc>,
+  task:    JoinHandle<()>
+}
+
+impl MockServer {
+  /// Serve `fixture` on a free local port
+  pub async fn start(fixture: Fixture) -> Self { Self::bind(fixture, "127.0.0.1:0").await.expect("Couldn't bind the mock server") }
+
+  /// Serve `fixture` with its event log swapped for `log`
+  pub async fn start_with_log(
+    fixture: Fixture,
+    log: LongLog
+  ) -> Self {
+    Self::listen(fixture, Some(log), "127.0.0.1:0")
+      .await
+      .expect("Couldn't bind the mock server")
+  }
+
+  pub async fn bind(
+    fixture: Fixture,
+    address: &str
+  ) -> io::Result<Self> {
+    Self::listen(fixture, None, address).await
+  }
+
+  async fn listen(
+    fixture: Fixture,
+    long_log: Option<LongLog>,
+    address: &str
+  ) -> io::Result<Self> {
+    let listener = TcpListener::bind(address).await?;
+    let address = listener.local_addr()?;
+
+    let bmc = Arc::new(Bmc {
+      fixture,
+      long_log,
+      failure: Mutex::new(None),
+      sessions: Mutex::new(HashMap::new()),
+      next_session: AtomicU32::new(0),
+      logins: AtomicU32::new(0),
+      requests: AtomicU32::new(0)
+    });
+    let app = Router::new().fallback(handle).with_state(bmc.clone());
+    let task = spawn(async move {
+      let _ = axum::serve(listener, app).await;
+    });
+
+    Ok(Self { address, bmc, task })
+  }
+
+  pub fn url(&self) -> String { format!("http://{}", self.address) }
+
+  /// A client logged in as [`USER`]
+  pub fn redfish_client(
+    &self,
+    auth: AuthMode
+  ) -> RedfishClient {
+    self.client_as(Credentials::new(USER, PASSWORD), auth)
+  }
+
+  pub fn client_as(
+    &self,
+    credentials: Credentials,
+    auth: AuthMode
+  ) -> RedfishClient {
+    RedfishClient::new(reqwest::Client::new(), &self.url(), credentials, auth)
+  }
+
+  /// Sessions created so far
+  pub fn logins(&self) -> u32 { self.bmc.logins.load(Ordering::SeqCst) }
+
+  /// Requests answered so far, including rejected ones
+  pub fn requests(&self) -> u32 { self.bmc.requests.load(Ordering::SeqCst) }
+
+  pub fn active_sessions(&self) -> usize { self.bmc.sessions.lock().unwrap().len() }
+
+  /// Answer everything but logins with `status` and a Redfish error body carrying `message`
+  pub fn fail_with(
+    &self,
+    status: StatusCode,
+    message: &'static str
+  ) {
+    *self.bmc.failure.lock().unwrap() = Some((status, message));
+  }
+
+  /// Drop every session, like a BMC that timed them out or rebooted
+  pub fn expire_sessions(&self) { self.bmc.sessions.lock().unwrap().clear() }
+}
+
+impl Drop for MockServer {
+  fn drop(&mut self) { self.task.abort() }
+}
@@ -0,0 +1,70 @@
+use serde_json::{
+  Value,
+  json
+};
+
+/// Entries per page, about what BMCs hand out
+const PAGE_SIZE: usize = 10;
+
+/// An event log served in place of the fixture's, entries `1..=entries` oldest first a minute apart.
+///
+/// Entries are dated within January 2025, so a month's worth of minutes at most.
+#[derive(Clone, Copy, Debug)]
+pub struct LongLog {
+  pub entries:      usize,
+  /// Whether `$skip` and `$top` are honoured, some BMCs ignore them and start from the first page
+  pub paging_query: bool
+}
+
+impl LongLog {
+  /// The page asked for by `query`, either `?page=n` or `$skip` and `$top`
+  pub(crate) fn page(
+    self,
+    path: &str,
+    query: Option<&str>
+  ) -> Value {
+    let param = |name: &str| {
+      query?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('=')?.parse::<usize>().ok())
+    };
+
+    let (skip, top, next) = match (param("$skip"), param("$top")) {
+      (skip, top) if self.paging_query && (skip.is_some() || top.is_some()) => {
+        let (skip, top) = (skip.unwrap_or(0), top.unwrap_or(PAGE_SIZE));
+        (skip, top, format!("{path}?$skip={}&$top={top}", skip + top))
+      },
+      _ => {
+        let page = param("page").unwrap_or(1).max(1);
+        ((page - 1) * PAGE_SIZE, PAGE_SIZE, format!("{path}?page={}", page + 1))
+      }
+    };
+
+    let end = (skip + top).min(self.entries);
+    let members: Vec<Value> = (skip + 1..=end).map(|id| entry(path, id)).collect();
+    let mut page = json!({
+      "@odata.id": path,
+      "Members": members,
+      "Members@odata.count": self.entries
+    });
+    if end < self.entries {
+      page["Members@odata.nextLink"] = next.into();
+    }
+
+    page
+  }
+}
+
+fn entry(
+  path: &str,
+  id: usize
+) -> Value {
+  json!({
+    "@odata.id": format!("{path}/{id}"),
+    "Id": id.to_string(),
+    "Created": format!("2025-01-{:02}T{:02}:{:02}:00Z", 1 + id / 1440, id % 1440 / 60, id % 60),
+    "Message": format!("Generated entry {id}"),
+    "Severity": "OK",
+    "EntryType": "Event"
+  })
+}
@@ -0,0 +1,43 @@
+// Serves recorded BMC responses for trying `/bmc` without a server
+// Usage: redfish-mock [ilo4|ilo5|idrac|supermicro] [address]
+// Point an [[ilo.hosts]] entry at "http://127.0.0.1:8443" and log in as kon/hunter2
+
+use {
+  kon_redfish_mock::{
+    Fixture,
+    MockServer,
+    PASSWORD,
+    USER
+  },
+  std::{
+    env::args,
+    process::exit
+  }
+};
+
+#[tokio::main]
+async fn main() {
+  let args: Vec<String> = args().collect();
+  let fixture = match args.get(1).map(String::as_str) {
+    Some("ilo4") => Fixture::Ilo4,
+    Some("ilo5") | None => Fixture::Ilo5,
+    Some("idrac") => Fixture::Idrac,
+    Some("supermicro") => Fixture::Supermicro,
+    Some(other) => {
+      eprintln!("Unknown fixture \"{other}\", expected ilo4, ilo5, idrac or supermicro");
+      exit(1);
+    }
+  };
+  let address = args.get(2).map_or("127.0.0.1:8443", String::as_str);
+
+  let server = match MockServer::bind(fixture, address).await {
+    Ok(server) => server,
+    Err(e) => {
+      eprintln!("Couldn't listen on {address}: {e}");
+      exit(1);
+    }
+  };
+
+  println!("Serving {fixture:?} on {} as {USER}/{PASSWORD}", server.url());
+  let _ = tokio::signal::ctrl_c().await;
+}
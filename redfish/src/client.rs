@@ -0,0 +1,541 @@
+use {
+  crate::{
+    Chassis,
+    ComputerSystem,
+    EventService,
+    LogEntry,
+    LogService,
+    Manager,
+    OdataId,
+    Power,
+    RedfishError,
+    RedfishResult,
+    ServiceRoot,
+    Thermal,
+    Vendor,
+    transport::{
+      Request,
+      Response,
+      Transport
+    },
+    types::CollectionPage
+  },
+  reqwest::{
+    Method,
+    StatusCode,
+    header::{
+      ACCEPT,
+      HeaderMap,
+      HeaderValue
+    }
+  },
+  serde::{
+    Deserialize,
+    de::DeserializeOwned
+  },
+  serde_json::{
+    Value,
+    json
+  },
+  std::{
+    cmp::{
+      Ordering,
+      Reverse
+    },
+    sync::Arc
+  },
+  tokio::sync::{
+    Mutex,
+    OnceCell
+  },
+  tracing::{
+    debug,
+    warn
+  }
+};
+
+const SERVICE_ROOT: &str = "/redfish/v1/";
+/// Where the sessions live if the service root doesn't link them
+const DEFAULT_SESSIONS: &str = "/redfish/v1/SessionService/Sessions";
+const AUTH_TOKEN: &str = "X-Auth-Token";
+/// Stops a BMC that keeps handing out `nextLink`s from looping forever
+const MAX_PAGES: usize = 100;
+
+/// How requests are authenticated
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+  /// Username and password on every request
+  #[default]
+  Basic,
+  /// Log in once and send the `X-Auth-Token`, logging in again when the session expires
+  Session
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+  pub user:     String,
+  pub password: String
+}
+
+impl Credentials {
+  pub fn new(
+    user: impl Into<String>,
+    password: impl Into<String>
+  ) -> Self {
+    Self {
+      user:     user.into(),
+      password: password.into()
+    }
+  }
+}
+
+struct Session {
+  token:    String,
+  /// Where to `DELETE` the session on logout
+  location: Option<String>
+}
+
+/// Talks to one BMC, cheap to keep around so a session gets reused
+pub struct RedfishClient {
+  transport:   Arc<dyn Transport>,
+  base:        String,
+  credentials: Credentials,
+  auth:        AuthMode,
+  session:     Mutex<Option<Session>>,
+  root:        OnceCell<ServiceRoot>
+}
+
+impl RedfishClient {
+  /// `address` is a host, `host:port` or a full `https://` URL,
+  /// a plain `reqwest::Client` works as the transport if nothing else is needed
+  pub fn new(
+    transport: impl Transport + 'static,
+    address: &str,
+    credentials: Credentials,
+    auth: AuthMode
+  ) -> Self {
+    let base = if address.contains("://") {
+      address.trim_end_matches('/').to_string()
+    } else {
+      format!("https://{}", address.trim_end_matches('/'))
+    };
+
+    Self {
+      transport: Arc::new(transport),
+      base,
+      credentials,
+      auth,
+      session: Mutex::new(None),
+      root: OnceCell::new()
+    }
+  }
+
+  pub fn base_url(&self) -> &str { &self.base }
+
+  pub fn credentials(&self) -> &Credentials { &self.credentials }
+
+  /// `@odata.id` paths are absolute, anything else is taken as relative to the service root
+  fn url(
+    &self,
+    path: &str
+  ) -> String {
+    if path.starts_with("http://") || path.starts_with("https://") {
+      path.to_string()
+    } else if path.starts_with('/') {
+      format!("{}{path}", self.base)
+    } else {
+      format!("{}{SERVICE_ROOT}{path}", self.base)
+    }
+  }
+
+  fn request(
+    &self,
+    method: Method,
+    url: &str
+  ) -> Request {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+
+    Request {
+      method,
+      url: url.to_string(),
+      headers,
+      basic_auth: None,
+      body: None
+    }
+  }
+
+  /// Fetched once and kept, it's readable without logging in
+  pub async fn service_root(&self) -> RedfishResult<&ServiceRoot> {
+    self
+      .root
+      .get_or_try_init(|| async {
+        let url = self.url(SERVICE_ROOT);
+        let res = self.transport.send(self.request(Method::GET, &url)).await?;
+
+        if !res.status.is_success() {
+          return Err(RedfishError::from_body(&url, res.status, &res.body));
+        }
+
+        decode(&url, &res.body)
+      })
+      .await
+  }
+
+  pub async fn vendor(&self) -> RedfishResult<Vendor> { Ok(Vendor::detect(self.service_root().await?)) }
+
+  pub async fn get<T: DeserializeOwned>(
+    &self,
+    path: &str
+  ) -> RedfishResult<T> {
+    let url = self.url(path);
+    let res = self.send(&url).await?;
+    decode(&url, &res.body)
+  }
+
+  /// Authenticated GET, a session that expired gets replaced once before giving up
+  async fn send(
+    &self,
+    url: &str
+  ) -> RedfishResult<Response> {
+    let mut logged_in_again = false;
+
+    loop {
+      let req = self.authenticate(self.request(Method::GET, url)).await?;
+      let res = self.transport.send(req).await?;
+
+      if res.status == StatusCode::UNAUTHORIZED && self.auth == AuthMode::Session && !logged_in_again {
+        debug!("Session on {} was rejected, logging in again", self.base);
+        self.session.lock().await.take();
+        logged_in_again = true;
+        continue;
+      }
+
+      if !res.status.is_success() {
+        return Err(RedfishError::from_body(url, res.status, &res.body));
+      }
+
+      return Ok(res);
+    }
+  }
+
+  async fn authenticate(
+    &self,
+    mut req: Request
+  ) -> RedfishResult<Request> {
+    match self.auth {
+      AuthMode::Basic => req.basic_auth = Some(self.credentials.clone()),
+      AuthMode::Session => {
+        let mut session = self.session.lock().await;
+        if session.is_none() {
+          *session = Some(self.login().await?);
+        }
+
+        let token = session.as_ref().map(|s| s.token.as_str()).unwrap_or_default();
+        let token = HeaderValue::from_str(token).map_err(|_| RedfishError::missing("usable X-Auth-Token"))?;
+        req.headers.insert(AUTH_TOKEN, token);
+      }
+    }
+
+    Ok(req)
+  }
+
+  async fn login(&self) -> RedfishResult<Session> {
+    let root = self.service_root().await?;
+    let sessions = root
+      .links
+      .as_ref()
+      .and_then(|links| links.sessions.as_ref())
+      .map_or(DEFAULT_SESSIONS, |link| link.id.as_str());
+    let url = self.url(sessions);
+
+    let mut req = self.request(Method::POST, &url);
+    req.body = Some(json!({ "UserName": self.credentials.user, "Password": self.credentials.password }));
+    let res = self.transport.send(req).await?;
+
+    if !res.status.is_success() {
+      return Err(RedfishError::from_body(&url, res.status, &res.body));
+    }
+
+    let header = |name| res.headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let token = header(AUTH_TOKEN).ok_or_else(|| RedfishError::missing("X-Auth-Token in the session response"))?;
+    let location = header("Location");
+
+    debug!("Logged into {}", self.base);
+    Ok(Session { token, location })
+  }
+
+  /// End the session, BMCs only allow a handful at a time
+  pub async fn logout(&self) {
+    let Some(session) = self.session.lock().await.take() else { return };
+    let Some(location) = session.location else { return };
+
+    let url = self.url(&location);
+    let mut req = self.request(Method::DELETE, &url);
+    if let Ok(token) = HeaderValue::from_str(&session.token) {
+      req.headers.insert(AUTH_TOKEN, token);
+    }
+
+    match self.transport.send(req).await {
+      Ok(res) if res.status.is_success() => debug!("Logged out of {}", self.base),
+      Ok(res) => debug!("Logout from {} answered with {}", self.base, res.status),
+      Err(e) => debug!("Couldn't log out of {}: {e}", self.base)
+    }
+  }
+
+  /// Every member of a collection, following `Members@odata.nextLink` across pages.
+  ///
+  /// Members that are only links are fetched one by one, expanded ones are used as they are.
+  pub async fn collection<T: DeserializeOwned>(
+    &self,
+    link: &OdataId
+  ) -> RedfishResult<Vec<T>> {
+    let mut members = Vec::new();
+    let mut next = Some(link.id.clone());
+    let mut pages = 0;
+
+    while let Some(page) = next.take() {
+      pages += 1;
+      if pages > MAX_PAGES {
+        warn!("{} has more than {MAX_PAGES} pages, ignoring the rest", link.id);
+        break;
+      }
+
+      let url = self.url(&page);
+      let page: CollectionPage = self.get(&page).await?;
+      next = page.next_link.clone();
+      members.extend(self.members(&url, page).await?);
+    }
+
+    Ok(members)
+  }
+
+  /// A page's members, fetching the ones that are only linked
+  async fn members<T: DeserializeOwned>(
+    &self,
+    url: &str,
+    page: CollectionPage
+  ) -> RedfishResult<Vec<T>> {
+    if let Some(items) = page.items.filter(|items| items.len() == page.members.len()) {
+      return items.into_iter().map(|item| from_value(url, item)).collect();
+    }
+
+    let mut members = Vec::with_capacity(page.members.len());
+    for member in page.members {
+      if is_link(&member) {
+        let link: OdataId = from_value(url, member)?;
+        members.push(self.get(&link.id).await?);
+      } else {
+        members.push(from_value(url, member)?);
+      }
+    }
+
+    Ok(members)
+  }
+
+  pub async fn systems(&self) -> RedfishResult<Vec<ComputerSystem>> {
+    let link = self.service_root().await?.systems.clone();
+    self.collection(&link.ok_or_else(|| RedfishError::missing("Systems collection"))?).await
+  }
+
+  /// The first system, BMCs manage a single one
+  pub async fn system(&self) -> RedfishResult<ComputerSystem> {
+    self.systems().await?.into_iter().next().ok_or_else(|| RedfishError::missing("systems"))
+  }
+
+  pub async fn chassis(&self) -> RedfishResult<Vec<Chassis>> {
+    let link = self.service_root().await?.chassis.clone();
+    self.collection(&link.ok_or_else(|| RedfishError::missing("Chassis collection"))?).await
+  }
+
+  /// Sensors of the first chassis that has any
+  pub async fn thermal(&self) -> RedfishResult<Thermal> {
+    let link = self.chassis().await?.into_iter().find_map(|chassis| chassis.thermal);
+    self
+      .get(&link.ok_or_else(|| RedfishError::missing("chassis with thermal data"))?.id)
+      .await
+  }
+
+  /// Readings of the first chassis that has any
+  pub async fn power(&self) -> RedfishResult<Power> {
+    let link = self.chassis().await?.into_iter().find_map(|chassis| chassis.power);
+    self.get(&link.ok_or_else(|| RedfishError::missing("chassis with power data"))?.id).await
+  }
+
+  pub async fn managers(&self) -> RedfishResult<Vec<Manager>> {
+    let link = self.service_root().await?.managers.clone();
+    self.collection(&link.ok_or_else(|| RedfishError::missing("Managers collection"))?).await
+  }
+
+  pub async fn event_service(&self) -> RedfishResult<EventService> {
+    let link = self.service_root().await?.event_service.clone();
+    self.get(&link.ok_or_else(|| RedfishError::missing("EventService"))?.id).await
+  }
+
+  /// A log service of the system by its `Id`, e.g. `IML` on iLO or `Sel` on iDRAC
+  pub async fn log_service(
+    &self,
+    system: &ComputerSystem,
+    id: &str
+  ) -> RedfishResult<LogService> {
+    let link = system.log_services.as_ref().ok_or_else(|| RedfishError::missing("LogServices"))?;
+    self
+      .collection::<LogService>(link)
+      .await?
+      .into_iter()
+      .find(|service| service.id.eq_ignore_ascii_case(id))
+      .ok_or_else(|| RedfishError::missing(format!("{id} log")))
+  }
+
+  /// The log the vendor keeps hardware events in, see [`Vendor::event_logs`].
+  ///
+  /// The managers' log services are only listed if the preferred one isn't the system's,
+  /// a vendor without known logs gets the system's first one.
+  pub async fn event_log(&self) -> RedfishResult<LogService> {
+    let ids = self.vendor().await?.event_logs();
+    let system = self.system().await?;
+    let mut services: Vec<LogService> = match &system.log_services {
+      Some(link) => self.collection(link).await?,
+      None => Vec::new()
+    };
+
+    let find = |services: &[LogService], id: &str| services.iter().position(|service| service.id.eq_ignore_ascii_case(id));
+    if ids.first().is_some_and(|id| find(&services, id).is_none()) {
+      for manager in self.managers().await? {
+        if let Some(link) = &manager.log_services {
+          services.extend(self.collection::<LogService>(link).await?);
+        }
+      }
+    }
+
+    ids
+      .iter()
+      .find_map(|id| find(&services, id))
+      .or((!services.is_empty()).then_some(0))
+      .map(|index| services.swap_remove(index))
+      .ok_or_else(|| RedfishError::missing("log services"))
+  }
+
+  pub async fn log_entries(
+    &self,
+    service: &LogService
+  ) -> RedfishResult<Vec<LogEntry>> {
+    let link = service
+      .entries
+      .as_ref()
+      .ok_or_else(|| RedfishError::missing(format!("entries in the {} log", service.id)))?;
+    self.collection(link).await
+  }
+
+  /// The newest `count` entries of a log, newest first.
+  ///
+  /// A log kept newest first only needs its first page, one kept oldest first is read from the end
+  /// with `$skip` and `$top`. A BMC that ignores those has its pages walked keeping only the newest
+  /// entries, up to [`MAX_PAGES`].
+  pub async fn latest_log_entries(
+    &self,
+    service: &LogService,
+    count: usize
+  ) -> RedfishResult<Vec<LogEntry>> {
+    let link = service
+      .entries
+      .as_ref()
+      .ok_or_else(|| RedfishError::missing(format!("entries in the {} log", service.id)))?;
+
+    let url = self.url(&link.id);
+    let first: CollectionPage = self.get(&link.id).await?;
+    let (mut next, total) = (first.next_link.clone(), first.count);
+    let mut entries: Vec<LogEntry> = self.members(&url, first).await?;
+
+    // Only the first and last entry of a page tell which end the newest are at
+    let order = match (
+      entries.first().and_then(LogEntry::created_at),
+      entries.last().and_then(LogEntry::created_at)
+    ) {
+      (Some(first), Some(last)) if entries.len() > 1 => Some(first.cmp(&last)),
+      _ => None
+    };
+
+    if next.is_some() && order != Some(Ordering::Greater) {
+      if let Some(total) = total.filter(|total| order == Some(Ordering::Less) && *total > count) {
+        match self.log_tail(&link.id, total - count, count).await {
+          // A BMC that ignores the query hands back the first page again
+          Ok(tail) if !tail.is_empty() && tail[0].id != entries[0].id => {
+            entries = tail;
+            next = None;
+          },
+          Ok(_) => debug!("{} ignores $skip, walking every page", link.id),
+          Err(e) => debug!("{} refuses $skip, walking every page: {e}", link.id)
+        }
+      }
+
+      let mut pages = 1;
+      while let Some(page) = next.take() {
+        pages += 1;
+        if pages > MAX_PAGES {
+          warn!("{} has more than {MAX_PAGES} pages, the newest entries may be missing", link.id);
+          break;
+        }
+
+        let url = self.url(&page);
+        let page: CollectionPage = self.get(&page).await?;
+        next = page.next_link.clone();
+        entries.extend(self.members::<LogEntry>(&url, page).await?);
+        newest(&mut entries, count);
+      }
+    }
+
+    newest(&mut entries, count);
+    Ok(entries)
+  }
+
+  /// `top` entries of a collection after the first `skip`
+  async fn log_tail(
+    &self,
+    link: &str,
+    skip: usize,
+    top: usize
+  ) -> RedfishResult<Vec<LogEntry>> {
+    let separator = if link.contains('?') { '&' } else { '?' };
+    let link = format!("{link}{separator}$skip={skip}&$top={top}");
+    let page: CollectionPage = self.get(&link).await?;
+    self.members(&self.url(&link), page).await
+  }
+}
+
+/// Keeps the newest `count` entries, newest first, entries without a usable timestamp go last
+fn newest(
+  entries: &mut Vec<LogEntry>,
+  count: usize
+) {
+  entries.sort_by_key(|entry| Reverse(entry.created_at()));
+  entries.truncate(count);
+}
+
+/// A member that's just `{"@odata.id": ...}` still has to be fetched
+fn is_link(member: &Value) -> bool {
+  member
+    .as_object()
+    .is_some_and(|object| object.len() == 1 && object.contains_key("@odata.id"))
+}
+
+fn from_value<T: DeserializeOwned>(
+  url: &str,
+  value: Value
+) -> RedfishResult<T> {
+  serde_json::from_value(value).map_err(|source| RedfishError::Decode {
+    url: url.to_string(),
+    source
+  })
+}
+
+fn decode<T: DeserializeOwned>(
+  url: &str,
+  body: &[u8]
+) -> RedfishResult<T> {
+  serde_json::from_slice(body).map_err(|source| RedfishError::Decode {
+    url: url.to_string(),
+    source
+  })
+}
@@ -0,0 +1,117 @@
+use {
+  reqwest::StatusCode,
+  serde::Deserialize,
+  std::{
+    error::Error,
+    fmt
+  }
+};
+
+pub type RedfishResult<T> = Result<T, RedfishError>;
+
+#[derive(Debug)]
+pub enum RedfishError {
+  /// The BMC couldn't be reached or the connection dropped, `source` is up to the transport
+  Http {
+    url:    String,
+    source: Box<dyn Error + Send + Sync>
+  },
+  /// The BMC answered with an error, `message` is from its Redfish error body
+  Status {
+    url:     String,
+    status:  StatusCode,
+    message: Option<String>
+  },
+  Decode {
+    url:    String,
+    source: serde_json::Error
+  },
+  /// Something the client looked for isn't in the service, e.g. a chassis without a Thermal link
+  Missing(String)
+}
+
+/// `{"error": {"message": ..., "@Message.ExtendedInfo": [{"Message": ...}]}}`
+#[derive(Deserialize)]
+struct ErrorBody {
+  error: ErrorDetail
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+  message:       Option<String>,
+  #[serde(rename = "@Message.ExtendedInfo", default)]
+  extended_info: Vec<ExtendedInfo>
+}
+
+#[derive(Deserialize)]
+struct ExtendedInfo {
+  #[serde(rename = "Message")]
+  message: Option<String>
+}
+
+impl RedfishError {
+  pub fn status(&self) -> Option<StatusCode> {
+    match self {
+      Self::Status { status, .. } => Some(*status),
+      _ => None
+    }
+  }
+
+  pub fn http(
+    url: &str,
+    source: impl Into<Box<dyn Error + Send + Sync>>
+  ) -> Self {
+    Self::Http {
+      url:    url.to_string(),
+      source: source.into()
+    }
+  }
+
+  pub(crate) fn missing(what: impl Into<String>) -> Self { Self::Missing(what.into()) }
+
+  /// Build a status error, the extended info is more specific than the generic message so it's preferred
+  pub(crate) fn from_body(
+    url: &str,
+    status: StatusCode,
+    body: &[u8]
+  ) -> Self {
+    let message = serde_json::from_slice::<ErrorBody>(body)
+      .ok()
+      .and_then(|body| body.error.extended_info.into_iter().find_map(|info| info.message).or(body.error.message));
+
+    Self::Status {
+      url: url.to_string(),
+      status,
+      message
+    }
+  }
+}
+
+impl fmt::Display for RedfishError {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    match self {
+      Self::Http { url, source } => write!(f, "Couldn't reach \"{url}\": {source}"),
+      Self::Status {
+        url,
+        status,
+        message: Some(message)
+      } => write!(f, "\"{url}\" answered with {status}: {message}"),
+      Self::Status { url, status, message: None } => write!(f, "\"{url}\" answered with {status}"),
+      Self::Decode { url, source } => write!(f, "Unexpected response from \"{url}\": {source}"),
+      Self::Missing(what) => write!(f, "The BMC has no {what}")
+    }
+  }
+}
+
+impl Error for RedfishError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::Http { source, .. } => Some(source.as_ref()),
+      Self::Decode { source, .. } => Some(source),
+      _ => None
+    }
+  }
+}
@@ -0,0 +1,33 @@
+//! Typed client for the Redfish API BMCs like HPE iLO, Dell iDRAC and Supermicro expose
+
+mod client;
+pub use client::{
+  AuthMode,
+  Credentials,
+  RedfishClient
+};
+
+mod error;
+pub use error::{
+  RedfishError,
+  RedfishResult
+};
+
+mod oem;
+pub use oem::{
+  DellSystem,
+  HpeSystem,
+  PostState,
+  SystemOem,
+  Vendor
+};
+
+mod transport;
+pub use transport::{
+  Request,
+  Response,
+  Transport
+};
+
+mod types;
+pub use types::*;
@@ -0,0 +1,122 @@
+use {
+  crate::{
+    ComputerSystem,
+    ServiceRoot
+  },
+  serde::Deserialize,
+  serde_json::Value
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vendor {
+  Hpe,
+  Dell,
+  Supermicro,
+  Other
+}
+
+impl Vendor {
+  /// From the service root's `Vendor`, or the OEM section on firmware older than Redfish 1.5
+  pub fn detect(root: &ServiceRoot) -> Self {
+    if let Some(vendor) = &root.vendor {
+      return match vendor.to_lowercase().as_str() {
+        "hpe" | "hp" => Self::Hpe,
+        "dell" | "dell inc." => Self::Dell,
+        "supermicro" => Self::Supermicro,
+        _ => Self::Other
+      };
+    }
+
+    if root.oem.contains_key("Hpe") || root.oem.contains_key("Hp") {
+      Self::Hpe
+    } else if root.oem.contains_key("Dell") {
+      Self::Dell
+    } else if root.oem.contains_key("Supermicro") {
+      Self::Supermicro
+    } else {
+      Self::Other
+    }
+  }
+
+  /// `Id`s of the log services hardware events end up in, most useful first
+  pub fn event_logs(self) -> &'static [&'static str] {
+    match self {
+      Self::Hpe => &["IML"],
+      // The lifecycle log also has config changes and firmware updates, the SEL only sensor events
+      Self::Dell => &["Lclog", "Sel"],
+      Self::Supermicro => &["Log1"],
+      Self::Other => &[]
+    }
+  }
+}
+
+/// How far the system is through POST, whichever way the vendor reports it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostState {
+  Finished,
+  InPost,
+  PowerOff,
+  Unknown
+}
+
+/// The vendor-specific part of a [`ComputerSystem`]
+#[derive(Clone, Debug)]
+pub enum SystemOem {
+  Hpe(HpeSystem),
+  Dell(DellSystem),
+  None
+}
+
+/// `Oem.Hpe` on iLO 5, `Oem.Hp` on iLO 4
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HpeSystem {
+  /// `FinishedPost`, `InPost`, `PowerOff`, ...
+  pub post_state: Option<String>,
+  pub power_regulator_mode: Option<String>,
+  pub intelligent_provisioning_version: Option<String>
+}
+
+/// `Oem.Dell.DellSystem` on iDRAC
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DellSystem {
+  pub system_generation:     Option<String>,
+  pub current_rollup_status: Option<String>,
+  pub bios_release_date:     Option<String>
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(value: &Value) -> Option<T> { serde_json::from_value(value.clone()).ok() }
+
+impl ComputerSystem {
+  pub fn oem(&self) -> SystemOem {
+    if let Some(hpe) = self.oem.get("Hpe").or_else(|| self.oem.get("Hp")).and_then(parse) {
+      return SystemOem::Hpe(hpe);
+    }
+
+    if let Some(dell) = self.oem.get("Dell").and_then(|dell| dell.get("DellSystem")).and_then(parse) {
+      return SystemOem::Dell(dell);
+    }
+
+    SystemOem::None
+  }
+
+  /// From HPE's `PostState`, else the standard `BootProgress`, else the power state
+  pub fn post_state(&self) -> PostState {
+    if let SystemOem::Hpe(HpeSystem { post_state: Some(state), .. }) = self.oem() {
+      return match state.as_str() {
+        "FinishedPost" => PostState::Finished,
+        "InPost" | "InPostDiscoveryComplete" => PostState::InPost,
+        "PowerOff" => PostState::PowerOff,
+        _ => PostState::Unknown
+      };
+    }
+
+    match self.boot_progress.as_ref().and_then(|progress| progress.last_state.as_deref()) {
+      Some("SystemHardwareInitializationComplete" | "OSBootStarted" | "OSRunning") => PostState::Finished,
+      Some(state) if state.ends_with("Started") || state == "SetupEntered" => PostState::InPost,
+      _ if self.power_state.as_deref() == Some("Off") => PostState::PowerOff,
+      _ => PostState::Unknown
+    }
+  }
+}
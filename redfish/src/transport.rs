@@ -0,0 +1,75 @@
+use {
+  crate::{
+    Credentials,
+    RedfishError,
+    RedfishResult
+  },
+  async_trait::async_trait,
+  reqwest::{
+    Client,
+    Method,
+    StatusCode,
+    header::HeaderMap
+  },
+  serde_json::Value
+};
+
+/// Largest body the plain `reqwest` transport reads
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+/// What the client wants sent, the transport decides how
+pub struct Request {
+  pub method:     Method,
+  pub url:        String,
+  pub headers:    HeaderMap,
+  pub basic_auth: Option<Credentials>,
+  /// Sent as JSON
+  pub body:       Option<Value>
+}
+
+/// A response read in full, whatever its status since Redfish explains errors in the body
+pub struct Response {
+  pub status:  StatusCode,
+  pub headers: HeaderMap,
+  pub body:    Vec<u8>
+}
+
+/// Gets requests to the BMC, lets an application put its own HTTP stack with retries and limits underneath
+#[async_trait]
+pub trait Transport: Send + Sync {
+  async fn send(
+    &self,
+    request: Request
+  ) -> RedfishResult<Response>;
+}
+
+/// Straight through `reqwest`, nothing is retried
+#[async_trait]
+impl Transport for Client {
+  async fn send(
+    &self,
+    request: Request
+  ) -> RedfishResult<Response> {
+    let url = request.url;
+    let mut req = self.request(request.method, &url).headers(request.headers);
+    if let Some(credentials) = &request.basic_auth {
+      req = req.basic_auth(&credentials.user, Some(&credentials.password));
+    }
+    if let Some(body) = &request.body {
+      req = req.json(body);
+    }
+
+    let mut res = req.send().await.map_err(|e| RedfishError::http(&url, e))?;
+    let (status, headers) = (res.status(), res.headers().clone());
+
+    let mut body = Vec::new();
+    while let Some(chunk) = res.chunk().await.map_err(|e| RedfishError::http(&url, e))? {
+      if body.len() + chunk.len() > MAX_BODY {
+        return Err(RedfishError::http(&url, format!("Response is larger than {MAX_BODY} bytes")));
+      }
+      body.extend_from_slice(&chunk);
+    }
+
+    Ok(Response { status, headers, body })
+  }
+}
@@ -0,0 +1,260 @@
+use {
+  chrono::{
+    DateTime,
+    FixedOffset,
+    NaiveDateTime
+  },
+  serde::Deserialize,
+  serde_json::{
+    Map,
+    Value
+  }
+};
+
+/// Link to another resource, everything in Redfish is discovered through these
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Hash)]
+pub struct OdataId {
+  #[serde(rename = "@odata.id")]
+  pub id: String
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Status {
+  pub health:        Option<String>,
+  pub health_rollup: Option<String>,
+  pub state:         Option<String>
+}
+
+/// `/redfish/v1/`, the only fixed path
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceRoot {
+  pub redfish_version: Option<String>,
+  /// Only there since Redfish 1.5, older firmware has to be told apart by its OEM section
+  pub vendor:          Option<String>,
+  pub product:         Option<String>,
+  pub systems:         Option<OdataId>,
+  pub chassis:         Option<OdataId>,
+  pub managers:        Option<OdataId>,
+  pub event_service:   Option<OdataId>,
+  pub session_service: Option<OdataId>,
+  pub links:           Option<RootLinks>,
+  #[serde(default)]
+  pub oem:             Map<String, Value>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RootLinks {
+  pub sessions: Option<OdataId>
+}
+
+/// A page of a collection, members are either links or already expanded
+#[derive(Deserialize)]
+pub(crate) struct CollectionPage {
+  #[serde(rename = "Members", default)]
+  pub members:   Vec<Value>,
+  #[serde(rename = "Members@odata.nextLink")]
+  pub next_link: Option<String>,
+  /// Members across every page
+  #[serde(rename = "Members@odata.count")]
+  pub count:     Option<usize>,
+  /// iLO 4 inlines the full members here
+  #[serde(rename = "Items")]
+  pub items:     Option<Vec<Value>>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ComputerSystem {
+  #[serde(rename = "@odata.id", default)]
+  pub odata_id:          String,
+  #[serde(default)]
+  pub id:                String,
+  pub manufacturer:      Option<String>,
+  pub model:             Option<String>,
+  pub power_state:       Option<String>,
+  pub status:            Option<Status>,
+  pub memory_summary:    Option<MemorySummary>,
+  /// Pre-standard HP field, iLO 4 firmware without `MemorySummary` only has this
+  pub memory:            Option<LegacyMemory>,
+  pub processor_summary: Option<ProcessorSummary>,
+  /// Redfish 1.13+, how far the boot got on firmware without an OEM POST state
+  pub boot_progress:     Option<BootProgress>,
+  pub log_services:      Option<OdataId>,
+  #[serde(default)]
+  pub oem:               Map<String, Value>
+}
+
+impl ComputerSystem {
+  pub fn total_memory_gib(&self) -> Option<f64> {
+    self
+      .memory_summary
+      .as_ref()
+      .and_then(|memory| memory.total_system_memory_gib)
+      .or_else(|| self.memory.as_ref().and_then(|memory| memory.total_system_memory_gb))
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MemorySummary {
+  #[serde(rename = "TotalSystemMemoryGiB")]
+  pub total_system_memory_gib: Option<f64>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LegacyMemory {
+  #[serde(rename = "TotalSystemMemoryGB")]
+  pub total_system_memory_gb: Option<f64>
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProcessorSummary {
+  pub count: Option<u32>,
+  pub model: Option<String>
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BootProgress {
+  /// `OSRunning`, `MemoryInitializationStarted`, `SystemHardwareInitializationComplete`, ...
+  pub last_state: Option<String>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Chassis {
+  #[serde(rename = "@odata.id", default)]
+  pub odata_id:     String,
+  #[serde(default)]
+  pub id:           String,
+  pub name:         Option<String>,
+  pub chassis_type: Option<String>,
+  pub thermal:      Option<OdataId>,
+  pub power:        Option<OdataId>
+}
+
+/// The BMC itself, iDRAC keeps its logs here rather than under the system
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Manager {
+  #[serde(rename = "@odata.id", default)]
+  pub odata_id:     String,
+  #[serde(default)]
+  pub id:           String,
+  pub model:        Option<String>,
+  pub log_services: Option<OdataId>
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Thermal {
+  #[serde(default)]
+  pub temperatures: Vec<Temperature>,
+  #[serde(default)]
+  pub fans:         Vec<Fan>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Temperature {
+  pub name:                     String,
+  pub reading_celsius:          Option<f64>,
+  pub upper_threshold_critical: Option<f64>,
+  pub upper_threshold_fatal:    Option<f64>,
+  pub status:                   Option<Status>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Fan {
+  /// iLO 4 calls it `FanName`
+  #[serde(alias = "FanName")]
+  pub name:          String,
+  #[serde(alias = "CurrentReading")]
+  pub reading:       Option<f64>,
+  /// `Percent` or `RPM`, iLO 4 leaves it out and means percent
+  #[serde(alias = "Units")]
+  pub reading_units: Option<String>,
+  pub status:        Option<Status>
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Power {
+  #[serde(default)]
+  pub power_control:        Vec<PowerControl>,
+  /// iLO 4 also reports the first power control's readings at the top level
+  pub power_capacity_watts: Option<f64>,
+  pub power_consumed_watts: Option<f64>,
+  pub power_metrics:        Option<PowerMetrics>
+}
+
+impl Power {
+  /// The chassis-wide readings, from `PowerControl` or the top-level fields older firmware has
+  pub fn summary(&self) -> PowerControl {
+    let control = self.power_control.first().cloned().unwrap_or_default();
+    PowerControl {
+      power_capacity_watts: control.power_capacity_watts.or(self.power_capacity_watts),
+      power_consumed_watts: control.power_consumed_watts.or(self.power_consumed_watts),
+      power_metrics:        control.power_metrics.or_else(|| self.power_metrics.clone())
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PowerControl {
+  pub power_capacity_watts: Option<f64>,
+  pub power_consumed_watts: Option<f64>,
+  pub power_metrics:        Option<PowerMetrics>
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PowerMetrics {
+  pub average_consumed_watts: Option<f64>,
+  pub max_consumed_watts:     Option<f64>,
+  pub min_consumed_watts:     Option<f64>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EventService {
+  pub service_enabled: Option<bool>,
+  pub status:          Option<Status>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LogService {
+  pub id:      String,
+  pub name:    Option<String>,
+  pub entries: Option<OdataId>
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LogEntry {
+  pub id:         Option<String>,
+  pub created:    Option<String>,
+  pub message:    Option<String>,
+  pub severity:   Option<String>,
+  pub entry_type: Option<String>,
+  #[serde(default)]
+  pub oem:        Map<String, Value>
+}
+
+impl LogEntry {
+  /// When the entry was logged, a timestamp without an offset is taken as UTC
+  pub fn created_at(&self) -> Option<DateTime<FixedOffset>> {
+    let created = self.created.as_deref()?;
+    DateTime::parse_from_rfc3339(created).ok().or_else(|| {
+      NaiveDateTime::parse_from_str(created, "%Y-%m-%dT%H:%M:%S")
+        .ok()
+        .map(|naive| naive.and_utc().fixed_offset())
+    })
+  }
+}
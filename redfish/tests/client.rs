@@ -0,0 +1,203 @@
+use {
+  kon_redfish::{
+    AuthMode,
+    Credentials,
+    PostState,
+    RedfishError,
+    SystemOem,
+    Vendor
+  },
+  kon_redfish_mock::{
+    Fixture,
+    LongLog,
+    MockServer,
+    USER
+  },
+  reqwest::StatusCode
+};
+
+#[tokio::test]
+async fn session_login_is_reused() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = server.redfish_client(AuthMode::Session);
+
+  client.system().await.unwrap();
+  client.thermal().await.unwrap();
+
+  assert_eq!(server.logins(), 1);
+  assert_eq!(server.active_sessions(), 1);
+
+  client.logout().await;
+  assert_eq!(server.active_sessions(), 0);
+}
+
+#[tokio::test]
+async fn expired_session_logs_in_again() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = server.redfish_client(AuthMode::Session);
+
+  client.power().await.unwrap();
+  server.expire_sessions();
+  client.power().await.unwrap();
+
+  assert_eq!(server.logins(), 2);
+}
+
+#[tokio::test]
+async fn ilo4_falls_back_to_default_sessions_path() {
+  let server = MockServer::start(Fixture::Ilo4).await;
+  let client = server.redfish_client(AuthMode::Session);
+
+  let system = client.system().await.unwrap();
+
+  assert_eq!(server.logins(), 1);
+  assert_eq!(system.total_memory_gib(), Some(64.0));
+}
+
+#[tokio::test]
+async fn basic_auth_never_logs_in() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = server.redfish_client(AuthMode::Basic);
+
+  client.system().await.unwrap();
+
+  assert_eq!(server.logins(), 0);
+}
+
+#[tokio::test]
+async fn pagination_follows_next_link() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = server.redfish_client(AuthMode::Basic);
+
+  let system = client.system().await.unwrap();
+  let iml = client.log_service(&system, "iml").await.unwrap();
+  let entries = client.log_entries(&iml).await.unwrap();
+
+  let ids: Vec<_> = entries.iter().filter_map(|entry| entry.id.as_deref()).collect();
+  assert_eq!(ids, ["1", "2", "3"]);
+}
+
+#[tokio::test]
+async fn latest_log_entries_walks_pages_without_paging_query() {
+  let log = LongLog {
+    entries:      50,
+    paging_query: false
+  };
+  let server = MockServer::start_with_log(Fixture::Ilo5, log).await;
+  let client = server.redfish_client(AuthMode::Basic);
+
+  let iml = client.event_log().await.unwrap();
+  let entries = client.latest_log_entries(&iml, 3).await.unwrap();
+
+  let ids: Vec<_> = entries.iter().filter_map(|entry| entry.id.as_deref()).collect();
+  assert_eq!(ids, ["50", "49", "48"]);
+}
+
+#[tokio::test]
+async fn latest_log_entries_when_paging_query_is_refused() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = server.redfish_client(AuthMode::Basic);
+
+  let iml = client.event_log().await.unwrap();
+  let entries = client.latest_log_entries(&iml, 2).await.unwrap();
+
+  let ids: Vec<_> = entries.iter().filter_map(|entry| entry.id.as_deref()).collect();
+  assert_eq!(ids, ["3", "2"]);
+}
+
+#[tokio::test]
+async fn ilo4_items_are_used_inline() {
+  let server = MockServer::start(Fixture::Ilo4).await;
+  let client = server.redfish_client(AuthMode::Basic);
+
+  let system = client.system().await.unwrap();
+  let iml = client.log_service(&system, "IML").await.unwrap();
+  let before = server.requests();
+  let entries = client.log_entries(&iml).await.unwrap();
+
+  assert_eq!(entries.len(), 2);
+  assert_eq!(server.requests() - before, 1);
+}
+
+#[tokio::test]
+async fn legacy_fields_are_read() {
+  let server = MockServer::start(Fixture::Ilo4).await;
+  let client = server.redfish_client(AuthMode::Basic);
+
+  let thermal = client.thermal().await.unwrap();
+  assert_eq!(thermal.fans[0].name, "Fan 1");
+  assert_eq!(thermal.fans[0].reading, Some(19.0));
+
+  let power = client.power().await.unwrap().summary();
+  assert_eq!(power.power_consumed_watts, Some(143.0));
+  assert_eq!(power.power_metrics.unwrap().max_consumed_watts, Some(176.0));
+}
+
+#[tokio::test]
+async fn vendor_and_oem_are_detected() {
+  for fixture in [Fixture::Ilo4, Fixture::Ilo5] {
+    let server = MockServer::start(fixture).await;
+    let client = server.redfish_client(AuthMode::Basic);
+
+    assert_eq!(client.vendor().await.unwrap(), Vendor::Hpe);
+    let SystemOem::Hpe(hpe) = client.system().await.unwrap().oem() else {
+      panic!("{fixture:?} has no HPE OEM section")
+    };
+    assert_eq!(hpe.post_state.as_deref(), Some("FinishedPost"));
+  }
+}
+
+#[tokio::test]
+async fn bad_credentials_are_rejected() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+
+  for auth in [AuthMode::Basic, AuthMode::Session] {
+    let client = server.client_as(Credentials::new(USER, "wrong"), auth);
+
+    let error = client.system().await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED), "{auth:?}");
+    assert!(matches!(error, RedfishError::Status { message: Some(_), .. }), "{auth:?}");
+  }
+
+  assert_eq!(server.logins(), 0);
+}
+
+#[tokio::test]
+async fn other_vendors_are_detected() {
+  for (fixture, vendor) in [(Fixture::Idrac, Vendor::Dell), (Fixture::Supermicro, Vendor::Supermicro)] {
+    let server = MockServer::start(fixture).await;
+    let client = server.redfish_client(AuthMode::Basic);
+
+    assert_eq!(client.vendor().await.unwrap(), vendor);
+    assert_eq!(client.system().await.unwrap().post_state(), PostState::Finished, "{fixture:?}");
+  }
+}
+
+#[tokio::test]
+async fn event_log_is_found_per_vendor() {
+  for (fixture, id) in [
+    (Fixture::Ilo4, "IML"),
+    (Fixture::Ilo5, "IML"),
+    (Fixture::Idrac, "Lclog"),
+    (Fixture::Supermicro, "Log1")
+  ] {
+    let server = MockServer::start(fixture).await;
+    let client = server.redfish_client(AuthMode::Basic);
+
+    assert_eq!(client.event_log().await.unwrap().id, id);
+  }
+}
+
+#[tokio::test]
+async fn error_body_is_kept() {
+  let server = MockServer::start(Fixture::Ilo5).await;
+  let client = server.redfish_client(AuthMode::Session);
+  server.fail_with(StatusCode::INTERNAL_SERVER_ERROR, "The BMC is out of resources.");
+
+  let error = client.system().await.unwrap_err();
+  assert_eq!(error.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+  assert!(
+    matches!(&error, RedfishError::Status { message: Some(message), .. } if message == "The BMC is out of resources."),
+    "{error}"
+  );
+}
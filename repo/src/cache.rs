@@ -1,37 +1,97 @@
-use kon_tokens::token_path;
-
 use {
   bb8_redis::{
     RedisConnectionManager,
-    bb8::Pool,
+    bb8::{
+      Pool,
+      PooledConnection,
+      RunError
+    },
     redis::{
       AsyncCommands,
+      Client,
+      ErrorKind,
       RedisError,
       RedisResult,
+      aio::PubSub,
       cmd
     }
   },
-  tokio::time::{
-    Duration,
-    sleep
+  kon_tokens::{
+    subscribe_secrets,
+    token_path
+  },
+  std::sync::{
+    Arc,
+    RwLock,
+    Weak
+  },
+  tokio::{
+    spawn,
+    time::{
+      Duration,
+      sleep
+    }
+  },
+  tracing::{
+    error,
+    info
   }
 };
 
 #[derive(Debug)]
+struct Backend {
+  uri:    String,
+  pool:   Pool<RedisConnectionManager>,
+  client: Client
+}
+
+/// Redis pool that gets rebuilt when the `redis_uri` secret rotates, clones share the same pool
+#[derive(Clone, Debug)]
 pub struct RedisController {
-  pool: Pool<RedisConnectionManager>
+  backend: Arc<RwLock<Backend>>
 }
 
 impl RedisController {
   pub async fn new() -> Result<Self, RedisError> {
-    let manager = RedisConnectionManager::new(token_path().await.redis_uri.as_str())?;
+    let backend = Arc::new(RwLock::new(Self::connect(token_path().await.redis_uri).await?));
+    spawn(Self::follow_rotation(Arc::downgrade(&backend)));
+    Ok(Self { backend })
+  }
+
+  async fn connect(uri: String) -> Result<Backend, RedisError> {
+    let manager = RedisConnectionManager::new(uri.as_str())?;
+    let client = Client::open(uri.as_str())?;
     let pool = Self::create_pool(manager).await;
-    Ok(Self { pool })
+    Ok(Backend { uri, pool, client })
+  }
+
+  /// Swap in a new pool whenever the rotated secrets carry a different URI,
+  /// stops once every clone of the controller has been dropped
+  async fn follow_rotation(backend: Weak<RwLock<Backend>>) {
+    let mut secrets = subscribe_secrets();
+
+    while secrets.changed().await.is_ok() {
+      let Some(backend) = backend.upgrade() else { return };
+      let Some(uri) = secrets.borrow_and_update().as_ref().map(|s| s.redis_uri.clone()) else {
+        continue;
+      };
+
+      if backend.read().unwrap().uri == uri {
+        continue;
+      }
+
+      match Self::connect(uri).await {
+        Ok(rotated) => {
+          *backend.write().unwrap() = rotated;
+          info!("Reconnected with the rotated credentials");
+        },
+        Err(e) => error!("Couldn't reconnect with the rotated credentials: {e}")
+      }
+    }
   }
 
   async fn create_pool(manager: RedisConnectionManager) -> Pool<RedisConnectionManager> {
     let mut backoff = 1;
-    let redis_err = "Redis[Error]: {{ e }}, retrying in {{ backoff }} seconds";
 
     loop {
       match Pool::builder().max_size(20).retry_connection(true).build(manager.clone()).await {
@@ -40,32 +100,22 @@ impl RedisController {
             let ping: RedisResult<String> = cmd("PING").query_async(&mut *conn).await;
             match ping {
               Ok(_) => {
-                println!("Redis[Info]: Successfully connected");
+                info!("Successfully connected");
                 return pool.clone();
               },
               Err(e) => {
-                eprintln!(
-                  "{}",
-                  redis_err
-                    .replace("{{ e }}", &e.to_string())
-                    .replace("{{ backoff }}", &backoff.to_string())
-                );
+                error!("{e}, retrying in {backoff} seconds");
                 Self::apply_backoff(&mut backoff).await;
               }
             }
           },
           Err(e) => {
-            eprintln!(
-              "{}",
-              redis_err
-                .replace("{{ e }}", &e.to_string())
-                .replace("{{ backoff }}", &backoff.to_string())
-            );
+            error!("{e}, retrying in {backoff} seconds");
             Self::apply_backoff(&mut backoff).await;
           }
         },
         Err(e) => {
-          eprintln!("Redis[PoolError]: {e}, retrying in {backoff} seconds");
+          error!("Couldn't build the pool: {e}, retrying in {backoff} seconds");
           Self::apply_backoff(&mut backoff).await;
         }
       }
@@ -79,12 +129,35 @@ impl RedisController {
     }
   }
 
+  /// Total and idle connections in the pool
+  pub fn pool_state(&self) -> (u32, u32) {
+    let state = self.backend.read().unwrap().pool.state();
+    (state.connections, state.idle_connections)
+  }
+
+  /// Check that the server answers
+  pub async fn ping(&self) -> Result<(), String> {
+    let mut conn = self.conn().await.map_err(|e| e.to_string())?;
+    let ping: RedisResult<String> = cmd("PING").query_async(&mut *conn).await;
+    ping.map(|_| ()).map_err(|e| e.to_string())
+  }
+
+  /// Borrow a raw connection from the pool for commands not covered below,
+  /// a pool that can't hand one out in time is an error like any other
+  pub(crate) async fn conn(&self) -> RedisResult<PooledConnection<'static, RedisConnectionManager>> {
+    let pool = self.backend.read().unwrap().pool.clone();
+    pool.get_owned().await.map_err(|e| match e {
+      RunError::User(e) => e,
+      RunError::TimedOut => RedisError::from((ErrorKind::IoError, "Timed out waiting for a pooled connection"))
+    })
+  }
+
   /// Get a key from the cache
   pub async fn get(
     &self,
     key: &str
   ) -> RedisResult<Option<String>> {
-    let mut conn = self.pool.get().await.unwrap();
+    let mut conn = self.conn().await?;
     conn.get(key).await
   }
 
@@ -92,7 +165,7 @@ impl RedisController {
     &self,
     key: &str
   ) -> RedisResult<()> {
-    let mut conn = self.pool.get().await.unwrap();
+    let mut conn = self.conn().await?;
     conn.del(key).await
   }
 
@@ -102,7 +175,7 @@ impl RedisController {
     key: &str,
     value: &str
   ) -> RedisResult<()> {
-    let mut conn = self.pool.get().await.unwrap();
+    let mut conn = self.conn().await?;
     conn.set(key, value).await
   }
 
@@ -112,7 +185,28 @@ impl RedisController {
     key: &str,
     seconds: i64
   ) -> RedisResult<()> {
-    let mut conn = self.pool.get().await.unwrap();
+    let mut conn = self.conn().await?;
     conn.expire(key, seconds).await
   }
+
+  /// Publish a message to a channel, returns how many subscribers received it
+  pub async fn publish(
+    &self,
+    channel: &str,
+    message: &str
+  ) -> RedisResult<i64> {
+    let mut conn = self.conn().await?;
+    conn.publish(channel, message).await
+  }
+
+  /// Open a dedicated pub/sub connection subscribed to a channel
+  pub async fn subscribe(
+    &self,
+    channel: &str
+  ) -> RedisResult<PubSub> {
+    let client = self.backend.read().unwrap().client.clone();
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(channel).await?;
+    Ok(pubsub)
+  }
 }
@@ -0,0 +1,112 @@
+use crate::RedisController;
+
+use {
+  bb8_redis::redis::{
+    RedisResult,
+    aio::PubSubStream
+  },
+  futures::StreamExt,
+  std::{
+    fmt,
+    str::FromStr
+  },
+  tracing::warn
+};
+
+/// Redis channel the bot listens on for admin commands
+pub const CONTROL_CHANNEL: &str = "Kon_Control";
+
+/// Admin commands that can be pushed to a running bot without going through Discord
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControlCommand {
+  PollFeed(String),
+  ClearCache(String)
+}
+
+impl ControlCommand {
+  pub const USAGE: &str = "poll <feed> | clear-cache <feed>";
+}
+
+impl fmt::Display for ControlCommand {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    match self {
+      Self::PollFeed(feed) => write!(f, "poll {feed}"),
+      Self::ClearCache(feed) => write!(f, "clear-cache {feed}")
+    }
+  }
+}
+
+impl FromStr for ControlCommand {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+
+    match parts[..] {
+      ["poll", feed] => Ok(Self::PollFeed(feed.to_string())),
+      ["clear-cache", feed] => Ok(Self::ClearCache(feed.to_string())),
+      _ => Err(format!("Unknown control command \"{s}\", expected: {}", Self::USAGE))
+    }
+  }
+}
+
+/// Subscriber side of the control channel
+pub struct ControlSubscriber(PubSubStream);
+
+impl ControlSubscriber {
+  pub async fn new(redis: &RedisController) -> RedisResult<Self> {
+    let pubsub = redis.subscribe(CONTROL_CHANNEL).await?;
+    Ok(Self(pubsub.into_on_message()))
+  }
+
+  /// Wait for the next valid command, returns `None` once the connection is gone
+  pub async fn next(&mut self) -> Option<ControlCommand> {
+    while let Some(msg) = self.0.next().await {
+      let payload: String = match msg.get_payload() {
+        Ok(payload) => payload,
+        Err(e) => {
+          warn!("Unreadable payload: {e}");
+          continue;
+        }
+      };
+
+      match payload.parse() {
+        Ok(command) => return Some(command),
+        Err(e) => warn!("{e}")
+      }
+    }
+
+    None
+  }
+}
+
+/// Publish a command to every bot instance listening on the control channel
+pub async fn publish_control(
+  redis: &RedisController,
+  command: &ControlCommand
+) -> RedisResult<i64> {
+  redis.publish(CONTROL_CHANNEL, &command.to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trip() {
+    for command in [ControlCommand::PollFeed("github".into()), ControlCommand::ClearCache("esxi".into())] {
+      assert_eq!(command.to_string().parse::<ControlCommand>(), Ok(command));
+    }
+  }
+
+  #[test]
+  fn rejects_missing_and_trailing_arguments() {
+    for command in ["", "poll", "clear-cache", "poll github esxi", "clear-cache esxi now", "reload"] {
+      assert!(command.parse::<ControlCommand>().is_err(), "\"{command}\" was accepted");
+    }
+    assert_eq!("  poll   github ".parse(), Ok(ControlCommand::PollFeed("github".into())));
+  }
+}
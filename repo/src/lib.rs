@@ -1,2 +1,19 @@
 mod cache;
 pub use cache::RedisController;
+
+mod control;
+pub use control::{
+  CONTROL_CHANNEL,
+  ControlCommand,
+  ControlSubscriber,
+  publish_control
+};
+
+mod lock;
+pub use lock::{
+  LeaderLock,
+  instance_id
+};
+
+mod settings;
+pub use settings::GuildSettings;
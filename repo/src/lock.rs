@@ -0,0 +1,98 @@
+use crate::RedisController;
+
+use {
+  bb8_redis::redis::{
+    RedisResult,
+    cmd
+  },
+  std::{
+    env::var,
+    sync::{
+      Arc,
+      LazyLock
+    }
+  },
+  tokio::time::Duration
+};
+
+/// Unique identity of this bot process, stored as the lock's value
+static INSTANCE_ID: LazyLock<String> = LazyLock::new(|| {
+  let host = var("HOSTNAME").unwrap_or_else(|_| "kon".to_string());
+  format!("{host}:{}", std::process::id())
+});
+
+// Only touch the key if we are still the one holding it
+const RENEW_SCRIPT: &str = r#"if redis.call("GET", KEYS[1]) == ARGV[1] then return redis.call("PEXPIRE", KEYS[1], ARGV[2]) else return 0 end"#;
+const RELEASE_SCRIPT: &str = r#"if redis.call("GET", KEYS[1]) == ARGV[1] then return redis.call("DEL", KEYS[1]) else return 0 end"#;
+
+pub fn instance_id() -> &'static str { INSTANCE_ID.as_str() }
+
+/// Redis-backed leader lock with a TTL, only one bot instance can hold a given name at a time.
+///
+/// The holder is expected to call [`LeaderLock::renew`] well within the TTL,
+/// if it dies the key expires and a standby instance can take over.
+pub struct LeaderLock {
+  redis: Arc<RedisController>,
+  key:   String,
+  ttl:   Duration
+}
+
+impl LeaderLock {
+  pub fn new(
+    redis: Arc<RedisController>,
+    name: &str,
+    ttl: Duration
+  ) -> Self {
+    Self {
+      redis,
+      key: format!("Lock_{name}"),
+      ttl
+    }
+  }
+
+  pub fn ttl(&self) -> Duration { self.ttl }
+
+  /// Try to become the holder, returns `false` if another instance has it
+  pub async fn acquire(&self) -> RedisResult<bool> {
+    let mut conn = self.redis.conn().await?;
+    let reply: Option<String> = cmd("SET")
+      .arg(&self.key)
+      .arg(instance_id())
+      .arg("NX")
+      .arg("PX")
+      .arg(self.ttl.as_millis() as u64)
+      .query_async(&mut *conn)
+      .await?;
+
+    Ok(reply.is_some())
+  }
+
+  /// Extend the TTL, returns `false` if the lock was lost to someone else
+  pub async fn renew(&self) -> RedisResult<bool> {
+    let mut conn = self.redis.conn().await?;
+    let renewed: i64 = cmd("EVAL")
+      .arg(RENEW_SCRIPT)
+      .arg(1)
+      .arg(&self.key)
+      .arg(instance_id())
+      .arg(self.ttl.as_millis() as u64)
+      .query_async(&mut *conn)
+      .await?;
+
+    Ok(renewed == 1)
+  }
+
+  /// Give up the lock early so a standby doesn't have to wait for the TTL
+  pub async fn release(&self) -> RedisResult<()> {
+    let mut conn = self.redis.conn().await?;
+    let _: i64 = cmd("EVAL")
+      .arg(RELEASE_SCRIPT)
+      .arg(1)
+      .arg(&self.key)
+      .arg(instance_id())
+      .query_async(&mut *conn)
+      .await?;
+
+    Ok(())
+  }
+}
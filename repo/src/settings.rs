@@ -0,0 +1,72 @@
+use crate::RedisController;
+
+use {
+  bb8_redis::redis::{
+    ErrorKind,
+    RedisError,
+    RedisResult
+  },
+  serde::{
+    Deserialize,
+    Serialize
+  },
+  std::collections::{
+    BTreeMap,
+    BTreeSet
+  }
+};
+
+/// Per-guild settings, anything left unset falls back to the bot-wide config
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+  /// Channel that receives command errors from this guild
+  pub log_channel:   Option<u64>,
+  pub embed_color:   Option<i32>,
+  /// Commands switched on or off by name, missing entries are enabled
+  pub features:      BTreeMap<String, bool>,
+  /// If not empty, only members with one of these roles can use the bot
+  pub allowed_roles: BTreeSet<u64>
+}
+
+impl GuildSettings {
+  pub fn is_enabled(
+    &self,
+    command: &str
+  ) -> bool {
+    self.features.get(command).copied().unwrap_or(true)
+  }
+}
+
+fn settings_key(guild_id: u64) -> String { format!("Guild_{guild_id}_Settings") }
+
+fn json_err(e: serde_json::Error) -> RedisError { RedisError::from((ErrorKind::TypeError, "Invalid guild settings", e.to_string())) }
+
+impl RedisController {
+  /// Stored settings for a guild, defaults if it never changed anything
+  pub async fn guild_settings(
+    &self,
+    guild_id: u64
+  ) -> RedisResult<GuildSettings> {
+    match self.get(&settings_key(guild_id)).await? {
+      Some(json) => serde_json::from_str(&json).map_err(json_err),
+      None => Ok(GuildSettings::default())
+    }
+  }
+
+  pub async fn set_guild_settings(
+    &self,
+    guild_id: u64,
+    settings: &GuildSettings
+  ) -> RedisResult<()> {
+    let key = settings_key(guild_id);
+
+    // No point keeping a key around for a guild that's back on defaults
+    if *settings == GuildSettings::default() {
+      return self.del(&key).await;
+    }
+
+    let json = serde_json::to_string(settings).map_err(json_err)?;
+    self.set(&key, &json).await
+  }
+}
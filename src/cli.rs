@@ -0,0 +1,33 @@
+use {
+  clap::Parser,
+  kon_libs::LogFormat,
+  std::path::PathBuf
+};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Kon, a Discord bot")]
+pub struct Cli {
+  /// Service name to fetch the secrets for, overrides `secrets.service` in the config
+  #[arg(short, long, env = "KON_SERVICE")]
+  pub service: Option<String>,
+
+  /// Config file to load
+  #[arg(short, long, env = "KON_CONFIG", default_value = kon_libs::DEFAULT_CONFIG_PATH)]
+  pub config: PathBuf,
+
+  /// Log output format, `text` or `json`
+  #[arg(long, env = "KON_LOG_FORMAT", default_value_t = LogFormat::Text)]
+  pub log_format: LogFormat,
+
+  /// Don't run the RSS task on this instance
+  #[arg(long)]
+  pub no_rss: bool,
+
+  /// Validate the config file and exit
+  #[arg(long)]
+  pub check_config: bool,
+
+  /// Register the slash commands globally once connected
+  #[arg(long)]
+  pub register_commands: bool
+}
@@ -1,11 +1,17 @@
 // https://cdn.toast-server.net/RustFSHiearchy.png
 // Using the new filesystem hierarchy
 
+mod cli;
+mod server;
+mod shutdown;
+
 #[cfg(feature = "rss")]
 use {
   kon_tasks::{
+    RSS_TASK,
     rss,
-    run_task
+    rss_schedule,
+    scheduler
   },
   std::{
     sync::Arc,
@@ -14,18 +20,42 @@ use {
 };
 
 use {
-  kon_cmds::register_cmds,
+  clap::Parser,
+  cli::Cli,
+  kon_cmds::{
+    canonical_name,
+    guild_check,
+    register_cmds
+  },
   kon_libs::{
     BINARY_PROPERTIES,
     BOT_VERSION,
+    ConfigMeta,
     GIT_COMMIT_BRANCH,
     GIT_COMMIT_HASH,
     KonData,
+    KonError,
     KonResult,
+    PoiseCtx,
     PoiseFwCtx,
-    mention_dev
+    apply_log_filter,
+    forward_logs,
+    init_logging,
+    mention_dev,
+    metrics::{
+      COMMAND_DURATION,
+      COMMAND_ERRORS,
+      COMMANDS_RUN
+    },
+    set_config_path,
+    watch_config
+  },
+  kon_tasks::listen_control,
+  kon_tokens::{
+    init_secrets,
+    refresh_secrets,
+    token_path
   },
-  kon_tokens::token_path,
   poise::serenity_prelude::{
     ChannelId,
     ClientBuilder,
@@ -39,49 +69,98 @@ use {
       CreateMessage
     }
   },
-  std::borrow::Cow
+  shutdown::{
+    graceful_shutdown,
+    shutting_down
+  },
+  std::{
+    borrow::Cow,
+    process::exit,
+    sync::OnceLock
+  },
+  tokio::{
+    spawn,
+    time::Instant
+  },
+  tracing::{
+    error,
+    info,
+    warn
+  }
 };
 
+static CLI: OnceLock<Cli> = OnceLock::new();
+
 async fn on_ready(
   ctx: &Context,
-  ready: &Ready
+  ready: &Ready,
+  framework: &poise::Framework<KonData, KonError>,
+  register_commands: bool
 ) -> KonResult<KonData> {
-  #[cfg(not(feature = "production"))]
-  {
-    println!("Event[Ready][Notice]: Detected a non-production environment!");
+  if BINARY_PROPERTIES.load().env.contains("dev") {
+    warn!("Detected a non-production environment!");
     let gateway = ctx.http.get_bot_gateway().await?;
     let session = gateway.session_start_limit;
-    println!("Event[Ready][Notice]: Session limit: {}/{}", session.remaining, session.total);
+    info!("Session limit: {}/{}", session.remaining, session.total);
   }
 
-  println!("Event[Ready]: Build version: {} ({GIT_COMMIT_HASH}:{GIT_COMMIT_BRANCH})", *BOT_VERSION);
-  println!("Event[Ready]: Connected to API as {}", ready.user.name);
+  info!("Build version: {} ({GIT_COMMIT_HASH}:{GIT_COMMIT_BRANCH})", *BOT_VERSION);
+  info!("Connected to API as {}", ready.user.name);
+
+  if register_commands {
+    let commands = &framework.options().commands;
+    poise::builtins::register_globally(ctx, commands).await?;
+    info!("Registered {} commands globally", commands.len());
+  }
 
   let message = CreateMessage::new();
   let ready_embed = CreateEmbed::new()
-    .color(BINARY_PROPERTIES.embed_color)
+    .color(BINARY_PROPERTIES.load().embed_color)
     .thumbnail(ready.user.avatar_url().unwrap_or_default())
     .author(CreateEmbedAuthor::new(format!("{} is ready!", ready.user.name)));
 
-  ChannelId::new(BINARY_PROPERTIES.ready_notify)
+  ChannelId::new(BINARY_PROPERTIES.load().ready_notify)
     .send_message(&ctx.http, message.add_embed(ready_embed))
     .await?;
 
-  Ok(KonData {})
+  KonData::new().await
+}
+
+async fn record_command(
+  ctx: PoiseCtx<'_>,
+  failed: bool
+) {
+  let command = canonical_name(&ctx.command().qualified_name);
+
+  if let Some(started) = ctx.invocation_data::<Instant>().await {
+    COMMAND_DURATION.with_label_values(&[&command]).observe(started.elapsed().as_secs_f64());
+  }
+
+  if failed {
+    COMMAND_ERRORS.with_label_values(&[&command]).inc();
+  }
 }
 
 async fn event_processor(
   framework: PoiseFwCtx<'_>,
   event: &FullEvent
 ) -> KonResult<()> {
-  #[cfg(feature = "rss")]
   if let FullEvent::Ready { .. } = event {
+    spawn(listen_control());
+    spawn(watch_config());
+  }
+
+  #[cfg(feature = "rss")]
+  if let FullEvent::Ready { .. } = event
+    && !CLI.get().is_some_and(|cli| cli.no_rss)
+  {
     let thread_id = format!("{:?}", current().id());
     let thread_num: String = thread_id.chars().filter(|c| c.is_ascii_digit()).collect();
-    println!("Event[Ready]: Task Scheduler operating on thread {thread_num}");
+    info!("Task Scheduler operating on thread {thread_num}");
 
     let ctx = Arc::new(framework.serenity_context.clone());
-    run_task(ctx.clone(), rss).await;
+    scheduler().register_scheduled(RSS_TASK, rss_schedule(), rss);
+    scheduler().start_all(ctx);
   }
 
   Ok(())
@@ -89,7 +168,32 @@ async fn event_processor(
 
 #[tokio::main]
 async fn main() {
-  let prefix = if BINARY_PROPERTIES.env.contains("dev") {
+  let cli = CLI.get_or_init(Cli::parse);
+  init_logging(cli.log_format);
+  set_config_path(cli.config.clone());
+
+  if cli.check_config {
+    match ConfigMeta::load(&cli.config) {
+      Ok(_) => info!("\"{}\" is valid", cli.config.display()),
+      Err(e) => {
+        error!("{e}");
+        exit(1);
+      }
+    }
+    return;
+  }
+
+  apply_log_filter(BINARY_PROPERTIES.load().log_filter.as_deref());
+
+  let mut secrets = BINARY_PROPERTIES.load().secrets.clone();
+  if let Some(service) = &cli.service {
+    secrets.service = service.clone();
+  }
+
+  init_secrets(&secrets);
+  spawn(refresh_secrets());
+
+  let prefix = if BINARY_PROPERTIES.load().env.contains("dev") {
     Some(Cow::Borrowed("kon!"))
   } else {
     Some(Cow::Borrowed("k!"))
@@ -112,14 +216,19 @@ async fn main() {
             Some(guild) => guild.name.clone(),
             None => String::from("DM/User-App")
           };
-          println!("Discord[{get_guild_name}]: {} ran /{}", ctx.author().name, ctx.command().qualified_name);
+          info!(guild = %get_guild_name, "{} ran /{}", ctx.author().name, ctx.command().qualified_name);
+
+          COMMANDS_RUN.with_label_values(&[&canonical_name(&ctx.command().qualified_name)]).inc();
+          ctx.set_invocation_data(Instant::now()).await;
         })
       },
+      post_command: |ctx| Box::pin(record_command(ctx, false)),
       on_error: |error| {
         Box::pin(async move {
           match error {
             poise::FrameworkError::Command { error, ctx, .. } => {
-              println!("PoiseCommandError({}): {error}", ctx.command().qualified_name);
+              error!(command = %ctx.command().qualified_name, "{error}");
+              record_command(ctx, true).await;
               ctx
                 .reply(format!(
                   "Encountered an error during command execution, ask {} to check console for more details!",
@@ -127,21 +236,46 @@ async fn main() {
                 ))
                 .await
                 .expect("Error sending message");
+
+              if let Some(guild_id) = ctx.guild_id()
+                && let Ok(settings) = ctx.data().guild_settings(guild_id.get()).await
+                && let Some(log_channel) = settings.log_channel
+              {
+                let report = CreateMessage::new().content(format!(
+                  "**/{}** failed for {}:```\n{error}\n```",
+                  ctx.command().qualified_name,
+                  ctx.author().name
+                ));
+
+                if let Err(y) = ChannelId::new(log_channel).send_message(ctx.http(), report).await {
+                  warn!(command = %ctx.command().qualified_name, "Couldn't report to the guild's log channel: {y}");
+                }
+              }
             },
-            poise::FrameworkError::EventHandler { error, event, .. } => println!("PoiseEventHandlerError({}): {error}", event.snake_case_name()),
-            poise::FrameworkError::UnknownInteraction { interaction, .. } => println!(
-              "PoiseUnknownInteractionError: {} tried to execute an unknown interaction ({})",
+            poise::FrameworkError::EventHandler { error, event, .. } => error!(event = event.snake_case_name(), "{error}"),
+            poise::FrameworkError::UnknownInteraction { interaction, .. } => warn!(
+              "{} tried to execute an unknown interaction ({})",
               interaction.user.name, interaction.data.name
             ),
-            other => println!("PoiseOtherError: {other}")
+            other => error!("{other}")
           }
         })
       },
+      command_check: Some(|ctx| {
+        Box::pin(async move {
+          if shutting_down() {
+            ctx.say("I'm restarting, try again in a minute!").await?;
+            return Ok(false);
+          }
+
+          guild_check(ctx).await
+        })
+      }),
       initialize_owners: true,
       event_handler: |framework, event| Box::pin(event_processor(framework, event)),
       ..Default::default()
     })
-    .setup(|ctx, ready, _| Box::pin(on_ready(ctx, ready)))
+    .setup(move |ctx, ready, framework| Box::pin(on_ready(ctx, ready, framework, cli.register_commands)))
     .build();
 
   let mut client = ClientBuilder::new(
@@ -152,7 +286,16 @@ async fn main() {
   .await
   .expect("Error creating client");
 
+  spawn(forward_logs(client.http.clone()));
+
+  if let Some(addr) = BINARY_PROPERTIES.load().server_addr {
+    spawn(server::serve(addr, client.shard_manager.clone()));
+    spawn(server::sample_gateway_latency(client.shard_manager.clone()));
+  }
+
+  spawn(graceful_shutdown(client.http.clone(), client.shard_manager.clone()));
+
   if let Err(why) = client.start().await {
-    println!("Error starting client: {why:#?}");
+    error!("Error starting client: {why:#?}");
   }
 }
@@ -0,0 +1,175 @@
+use {
+  crate::shutdown::shutting_down,
+  axum::{
+    Json,
+    Router,
+    extract::State,
+    http::{
+      StatusCode,
+      header::CONTENT_TYPE
+    },
+    response::IntoResponse,
+    routing::get
+  },
+  kon_libs::metrics::{
+    GATEWAY_LATENCY,
+    render
+  },
+  kon_tasks::{
+    TaskState,
+    redis_if_connected,
+    scheduler
+  },
+  poise::serenity_prelude::{
+    ConnectionStage,
+    ShardManager
+  },
+  serde_json::{
+    Value,
+    json
+  },
+  std::{
+    net::SocketAddr,
+    sync::Arc
+  },
+  tokio::{
+    net::TcpListener,
+    time::{
+      Duration,
+      Instant,
+      interval,
+      timeout
+    }
+  },
+  tracing::{
+    error,
+    info
+  }
+};
+
+/// How often the shard heartbeat latencies are copied into the metrics
+const LATENCY_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A Redis ping taking longer than this counts as unreachable
+const REDIS_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
+struct ServerState {
+  started:       Instant,
+  shard_manager: Arc<ShardManager>
+}
+
+async fn metrics() -> impl IntoResponse { ([(CONTENT_TYPE, "text/plain; version=0.0.4")], render()) }
+
+/// The process is up and serving requests
+async fn healthz(State(state): State<ServerState>) -> Json<Value> {
+  Json(json!({
+    "status": "ok",
+    "uptime_secs": state.started.elapsed().as_secs()
+  }))
+}
+
+fn check(
+  ok: bool,
+  detail: impl Into<String>
+) -> Value {
+  json!({ "ok": ok, "detail": detail.into() })
+}
+
+async fn gateway_check(shard_manager: &ShardManager) -> Value {
+  let runners = shard_manager.runners.lock().await;
+  let connected = runners.values().filter(|runner| runner.stage == ConnectionStage::Connected).count();
+
+  check(
+    !runners.is_empty() && connected == runners.len(),
+    format!("{connected}/{} shards connected", runners.len())
+  )
+}
+
+/// Pings the connection the tasks share rather than opening one just for this
+async fn redis_check() -> Value {
+  let Some(redis) = redis_if_connected() else {
+    return check(false, "Still connecting");
+  };
+
+  match timeout(REDIS_TIMEOUT, redis.ping()).await {
+    Ok(Ok(())) => check(true, "PONG"),
+    Ok(Err(y)) => check(false, y),
+    Err(_) => check(false, format!("No reply within {}s", REDIS_TIMEOUT.as_secs()))
+  }
+}
+
+/// Paused tasks and tasks another instance is running on are fine, anything else has to be up
+fn tasks_check() -> Value {
+  let unhealthy: Vec<String> = scheduler()
+    .tasks()
+    .into_iter()
+    .filter(|task| !task.paused && !matches!(task.state, TaskState::Running | TaskState::Standby | TaskState::Finished))
+    .map(|task| format!("{} is {}", task.name, task.state))
+    .collect();
+
+  if unhealthy.is_empty() {
+    check(true, "All tasks are running")
+  } else {
+    check(false, unhealthy.join(", "))
+  }
+}
+
+/// Ready to take traffic: connected to Discord, Redis answering and tasks running
+async fn readyz(State(state): State<ServerState>) -> impl IntoResponse {
+  let checks = json!({
+    "gateway": gateway_check(&state.shard_manager).await,
+    "redis": redis_check().await,
+    "tasks": tasks_check(),
+    "shutdown": check(!shutting_down(), if shutting_down() { "Shutting down" } else { "Not shutting down" })
+  });
+
+  let ready = checks.as_object().is_some_and(|checks| checks.values().all(|check| check["ok"] == true));
+  let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+  (status, Json(json!({ "ready": ready, "checks": checks })))
+}
+
+/// Serve the HTTP endpoints on the configured address
+pub async fn serve(
+  addr: SocketAddr,
+  shard_manager: Arc<ShardManager>
+) {
+  let state = ServerState {
+    started: Instant::now(),
+    shard_manager
+  };
+
+  let app = Router::new()
+    .route("/healthz", get(healthz))
+    .route("/readyz", get(readyz))
+    .route("/metrics", get(metrics))
+    .with_state(state);
+
+  let listener = match TcpListener::bind(addr).await {
+    Ok(listener) => listener,
+    Err(y) => {
+      error!("Couldn't listen on {addr}: {y}");
+      return;
+    }
+  };
+
+  info!("Listening on {addr}");
+  if let Err(y) = axum::serve(listener, app).await {
+    error!("Server stopped: {y}");
+  }
+}
+
+pub async fn sample_gateway_latency(shard_manager: Arc<ShardManager>) {
+  let mut interval = interval(LATENCY_SAMPLE_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    for (id, runner) in shard_manager.runners.lock().await.iter() {
+      if let Some(latency) = runner.latency {
+        GATEWAY_LATENCY.with_label_values(&[&id.to_string()]).set(latency.as_secs_f64());
+      }
+    }
+  }
+}
@@ -0,0 +1,88 @@
+use {
+  kon_cmds::finish_conversions,
+  kon_libs::BINARY_PROPERTIES,
+  kon_tasks::{
+    persist_state,
+    scheduler
+  },
+  poise::serenity_prelude::{
+    ChannelId,
+    Http,
+    ShardManager,
+    builder::{
+      CreateEmbed,
+      CreateMessage
+    }
+  },
+  std::sync::{
+    Arc,
+    atomic::{
+      AtomicBool,
+      Ordering
+    }
+  },
+  tokio::{
+    join,
+    select,
+    signal::{
+      ctrl_c,
+      unix::{
+        SignalKind,
+        signal
+      }
+    },
+    time::Duration
+  },
+  tracing::{
+    error,
+    info
+  }
+};
+
+/// How long running tasks and conversions get to finish before they're cut off
+const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(20);
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Whether the bot has started shutting down and shouldn't take on new commands
+pub fn shutting_down() -> bool { SHUTTING_DOWN.load(Ordering::SeqCst) }
+
+async fn wait_for_signal() -> &'static str {
+  let mut sigterm = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+
+  select! {
+    _ = sigterm.recv() => "SIGTERM",
+    _ = ctrl_c() => "SIGINT"
+  }
+}
+
+/// Wait for SIGTERM/SIGINT, then wind everything down and close the shards
+pub async fn graceful_shutdown(
+  http: Arc<Http>,
+  shard_manager: Arc<ShardManager>
+) {
+  let signal = wait_for_signal().await;
+  SHUTTING_DOWN.store(true, Ordering::SeqCst);
+  info!("Received {signal}, waiting up to {}s for running work", SHUTDOWN_DEADLINE.as_secs());
+
+  let notice = CreateEmbed::new()
+    .color(BINARY_PROPERTIES.load().embed_color)
+    .title("Shutting down")
+    .description(format!("Received {signal}, finishing running tasks before going offline"));
+
+  if let Err(y) = ChannelId::new(BINARY_PROPERTIES.load().kon_logs)
+    .send_message(&http, CreateMessage::new().add_embed(notice))
+    .await
+  {
+    error!("Couldn't post the shutdown notice: {y}");
+  }
+
+  // Tasks release their leader locks on the way out so a standby instance can take over right away
+  join!(scheduler().shutdown(SHUTDOWN_DEADLINE), finish_conversions(SHUTDOWN_DEADLINE));
+
+  info!("Saving task state");
+  persist_state().await;
+
+  info!("Closing shards");
+  shard_manager.shutdown_all().await;
+}
@@ -0,0 +1,82 @@
+use super::get_redis;
+
+use {
+  kon_repo::{
+    ControlCommand,
+    ControlSubscriber
+  },
+  std::sync::atomic::{
+    AtomicBool,
+    Ordering
+  },
+  tokio::time::{
+    Duration,
+    sleep
+  },
+  tracing::{
+    error,
+    info,
+    warn
+  }
+};
+
+const TASK_NAME: &str = "Control";
+
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+/// Hand a command to the task it belongs to, only if that task is running on this instance
+fn dispatch(command: ControlCommand) {
+  match command {
+    ControlCommand::PollFeed(_) | ControlCommand::ClearCache(_) => {
+      #[cfg(feature = "rss")]
+      {
+        use super::{
+          TaskState,
+          rss,
+          scheduler
+        };
+
+        if scheduler().state(rss::TASK_NAME) != Some(TaskState::Running) {
+          info!(task = TASK_NAME, "{} task isn't running on this instance, ignoring", rss::TASK_NAME);
+          return;
+        }
+
+        rss::queue_control(command);
+        if let Err(y) = scheduler().trigger(rss::TASK_NAME) {
+          error!(task = TASK_NAME, "{y}");
+        }
+      }
+
+      #[cfg(not(feature = "rss"))]
+      error!(task = TASK_NAME, "\"{command}\" needs the RSS task, which isn't compiled in");
+    }
+  }
+}
+
+/// Listen on the Redis control channel and hand commands off to the tasks,
+/// reconnects if the subscription drops
+pub async fn listen_control() {
+  if LISTENING.swap(true, Ordering::SeqCst) {
+    return;
+  }
+
+  loop {
+    let redis = get_redis().await;
+
+    match ControlSubscriber::new(&redis).await {
+      Ok(mut subscriber) => {
+        info!(task = TASK_NAME, "Listening for admin commands");
+
+        while let Some(command) = subscriber.next().await {
+          info!(task = TASK_NAME, "Received \"{command}\"");
+          dispatch(command);
+        }
+
+        warn!(task = TASK_NAME, "Subscription closed, resubscribing");
+      },
+      Err(y) => error!(task = TASK_NAME, "Failed to subscribe: {y}")
+    }
+
+    sleep(Duration::from_secs(5)).await;
+  }
+}
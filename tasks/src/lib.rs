@@ -2,61 +2,59 @@
 mod rss;
 
 #[cfg(feature = "rss")]
-pub use rss::rss;
+pub use rss::{
+  TASK_NAME as RSS_TASK,
+  rss,
+  rss_schedule
+};
+
+mod control;
+pub use control::listen_control;
+
+mod schedule;
+pub use schedule::{
+  MissedRun,
+  TaskSchedule
+};
+
+mod scheduler;
+pub use scheduler::{
+  Scheduler,
+  TaskInfo,
+  TaskState,
+  TaskStats,
+  scheduler
+};
 
 use {
-  kon_libs::KonResult,
-  poise::serenity_prelude::Context,
-  std::{
-    future::Future,
-    sync::{
-      Arc,
-      atomic::{
-        AtomicBool,
-        Ordering
-      }
-    }
-  },
-  tokio::task::spawn
+  kon_libs::metrics::track_redis_pool,
+  kon_repo::RedisController,
+  std::sync::Arc,
+  tokio::sync::OnceCell
 };
 
-#[cfg(feature = "rss")]
-fn task_info(
-  name: &str,
-  message: &str
-) {
-  println!("TaskScheduler[{name}]: {message}")
-}
+static REDIS_SERVICE: OnceCell<Arc<RedisController>> = OnceCell::const_new();
 
-#[cfg(feature = "rss")]
-fn task_err(
-  name: &str,
-  message: &str
-) {
-  eprintln!("TaskScheduler[{name}:Error]: {message}")
+/// Callers racing on the first use wait for the same connection instead of each making one
+async fn get_redis() -> Arc<RedisController> {
+  REDIS_SERVICE
+    .get_or_init(|| async {
+      let redis = RedisController::new().await.unwrap();
+      track_redis_pool("tasks", redis.clone());
+      Arc::new(redis)
+    })
+    .await
+    .clone()
 }
 
-static TASK_RUNNING: AtomicBool = AtomicBool::new(false);
-
-pub async fn run_task<F, T>(
-  ctx: Arc<Context>,
-  task: F
-) where
-  F: Fn(Arc<Context>) -> T + Send + 'static,
-  T: Future<Output = KonResult<()>> + Send + 'static
-{
-  let ctx_cl = Arc::clone(&ctx);
-
-  if !TASK_RUNNING.load(Ordering::SeqCst) {
-    TASK_RUNNING.store(true, Ordering::SeqCst);
-    spawn(async move {
-      if let Err(y) = task(ctx_cl).await {
-        eprintln!("TaskScheduler[Main:Error]: Failed to execute the task, error reason: {y}");
-        if let Some(source) = y.source() {
-          eprintln!("TaskScheduler[Main:Error]: Failed to execute the task, this is caused by: {source:#?}");
-        }
-      }
-      TASK_RUNNING.store(false, Ordering::SeqCst);
-    });
-  }
+/// Save what the tasks still hold in memory to Redis, meant to run once they've all stopped on shutdown.
+/// Feeds write their own state as they post, so what's left is the scheduler's bookkeeping and queued commands.
+pub async fn persist_state() {
+  scheduler().persist().await;
+
+  #[cfg(feature = "rss")]
+  rss::persist_pending_control().await;
 }
+
+/// The shared connection once something has set it up, for callers that shouldn't wait on it or panic without it
+pub fn redis_if_connected() -> Option<Arc<RedisController>> { REDIS_SERVICE.get().cloned() }
@@ -13,18 +13,19 @@ use {
 };
 
 use super::{
-  task_err,
-  task_info
+  TaskSchedule,
+  get_redis,
+  redis_if_connected
 };
 
 use {
   feed_rs::parser::parse,
   kon_libs::{
+    BINARY_PROPERTIES,
     HttpClient,
     KonResult
   },
-  kon_repo::RedisController,
-  once_cell::sync::OnceCell,
+  kon_repo::ControlCommand,
   poise::serenity_prelude::{
     Context,
     CreateEmbed,
@@ -32,32 +33,24 @@ use {
     async_trait
   },
   regex::Regex,
-  reqwest::Response,
-  std::sync::Arc,
-  tokio::time::{
-    Duration,
-    interval
-  }
+  std::sync::{
+    Arc,
+    LazyLock,
+    Mutex
+  },
+  tokio::{
+    sync::Mutex as AsyncMutex,
+    time::Duration
+  },
+  tracing::error
 };
 
 pub type RSSFeedBox = Box<dyn RSSFeed + Send + Sync>;
 
-const TASK_NAME: &str = "RSS";
+pub const TASK_NAME: &str = "RSS";
 static REDIS_EXPIRY_SECS: i64 = 7200;
-static REDIS_SERVICE: OnceCell<Arc<RedisController>> = OnceCell::new();
-
-async fn redis_() {
-  let redis = RedisController::new().await.unwrap();
-  REDIS_SERVICE.set(Arc::new(redis)).unwrap();
-}
-
-async fn get_redis() -> Arc<RedisController> {
-  if REDIS_SERVICE.get().is_none() {
-    redis_().await;
-  }
-  REDIS_SERVICE.get().unwrap().clone()
-}
-
+static PROCESSOR: LazyLock<AsyncMutex<processor::RSSProcessor>> = LazyLock::new(|| AsyncMutex::new(build_processor()));
+static PENDING_CONTROL: LazyLock<Mutex<Vec<ControlCommand>>> = LazyLock::new(|| Mutex::new(Vec::new()));
 fn format_href_to_discord(input: &str) -> String {
   let re = Regex::new(r#"<a href="([^"]+)">([^<]+)</a>"#).unwrap();
   re.replace_all(input, r"[$2]($1)").to_string()
@@ -87,15 +80,7 @@ fn format_html_to_discord(input: String) -> String {
   output
 }
 
-async fn fetch_feed(url: &str) -> KonResult<Response> {
-  let http = HttpClient::new();
-  let res = match http.get(url, "RSS-Monitor").await {
-    Ok(res) => res,
-    Err(y) => return Err(y.into())
-  };
-
-  Ok(res)
-}
+async fn fetch_feed(url: &str) -> KonResult<String> { Ok(HttpClient::shared().get(url).user_agent("RSS-Monitor").text().await?) }
 
 async fn save_to_redis(
   key: &str,
@@ -104,7 +89,7 @@ async fn save_to_redis(
   let redis = get_redis().await;
   redis.set(key, value).await.unwrap();
   if let Err(y) = redis.expire(key, REDIS_EXPIRY_SECS).await {
-    task_err("RSS", format!("[RedisExpiry]: {y}").as_str());
+    error!(task = TASK_NAME, "Failed to set the cache expiry: {y}");
   }
   Ok(())
 }
@@ -170,14 +155,7 @@ pub enum RSSFeedOutput {
   Content(String)
 }
 
-pub async fn rss(ctx: Arc<Context>) -> KonResult<()> {
-  #[cfg(feature = "production")]
-  let mut interval = interval(Duration::from_secs(300)); // Check feeds every 5 mins
-  #[cfg(not(feature = "production"))]
-  let mut interval = interval(Duration::from_secs(30)); // Check feeds every 30 secs
-  let mut first_run = true;
-  task_info(TASK_NAME, "Task loaded!");
-
+fn build_processor() -> processor::RSSProcessor {
   let feeds: Vec<RSSFeedBox> = vec![
     Box::new(Esxi::new("https://esxi-patches.v-front.de/atom/ESXi-7.0.0.xml".to_string())),
     Box::new(GitHub::new("https://www.githubstatus.com/history.atom".to_string())),
@@ -191,16 +169,106 @@ pub async fn rss(ctx: Arc<Context>) -> KonResult<()> {
     processor.add_feed(feed);
   }
 
-  loop {
-    interval.tick().await;
+  processor
+}
+
+pub fn rss_schedule() -> TaskSchedule {
+  TaskSchedule::every(Duration::from_secs(BINARY_PROPERTIES.load().rss_interval)).jitter(Duration::from_secs(5))
+}
+
+/// Queue up a control command for the next run, caller is expected to trigger the task
+pub(crate) fn queue_control(command: ControlCommand) { PENDING_CONTROL.lock().unwrap().push(command); }
+
+/// Redis key holding control commands that were queued but didn't get a run before the last shutdown
+const PARKED_CONTROL_KEY: &str = "RSS_ParkedControl";
+
+fn park(commands: &[ControlCommand]) -> String { commands.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n") }
+
+fn unpark(parked: &str) -> Vec<ControlCommand> {
+  parked
+    .lines()
+    .filter_map(|line| match line.parse() {
+      Ok(command) => Some(command),
+      Err(e) => {
+        error!(task = TASK_NAME, "Dropping a parked command: {e}");
+        None
+      }
+    })
+    .collect()
+}
+
+/// Save the control commands still waiting on a run to Redis, the next run after a restart picks them up
+pub(crate) async fn persist_pending_control() {
+  let commands = std::mem::take(&mut *PENDING_CONTROL.lock().unwrap());
+  if commands.is_empty() {
+    return;
+  }
 
-    if first_run {
-      task_info(&format!("{TASK_NAME}:Processor"), "Starting up!");
-      first_run = false;
+  let Some(redis) = redis_if_connected() else {
+    error!(
+      task = TASK_NAME,
+      "Redis was never connected, dropping {} queued command(s)",
+      commands.len()
+    );
+    return;
+  };
+
+  if let Err(y) = redis.set(PARKED_CONTROL_KEY, &park(&commands)).await {
+    error!(task = TASK_NAME, "Failed to save {} queued command(s): {y}", commands.len());
+  }
+}
+
+/// Commands parked by the previous shutdown, removed from Redis once read
+async fn take_parked_control() -> Vec<ControlCommand> {
+  let redis = get_redis().await;
+
+  match redis.get(PARKED_CONTROL_KEY).await {
+    Ok(Some(parked)) => {
+      if let Err(y) = redis.del(PARKED_CONTROL_KEY).await {
+        error!(task = TASK_NAME, "Failed to clear the parked commands: {y}");
+      }
+      unpark(&parked)
+    },
+    Ok(None) => Vec::new(),
+    Err(y) => {
+      error!(task = TASK_NAME, "Failed to read the parked commands: {y}");
+      Vec::new()
     }
+  }
+}
+
+/// A single RSS run, handles any queued control commands instead of polling every feed
+pub async fn rss(ctx: Arc<Context>) -> KonResult<()> {
+  let processor = PROCESSOR.lock().await;
+  let mut commands = take_parked_control().await;
+  commands.append(&mut std::mem::take(&mut *PENDING_CONTROL.lock().unwrap()));
+
+  if commands.is_empty() {
+    return processor.process_all(ctx).await;
+  }
 
-    if let Err(e) = processor.process_all(ctx.clone()).await {
-      task_err(&format!("{TASK_NAME}:Processor"), &e.to_string());
+  for command in commands {
+    let result = match command {
+      ControlCommand::PollFeed(name) => processor.process_by_name(ctx.clone(), &name).await,
+      ControlCommand::ClearCache(name) => processor.clear_cache(&name).await
+    };
+
+    if let Err(e) = result {
+      error!(task = TASK_NAME, "{e}");
     }
   }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parked_commands_round_trip() {
+    let commands = vec![ControlCommand::PollFeed("GitHub".into()), ControlCommand::ClearCache("ESXi".into())];
+    assert_eq!(unpark(&park(&commands)), commands);
+    assert_eq!(unpark("poll GitHub\nbogus\n"), vec![ControlCommand::PollFeed("GitHub".into())]);
+  }
 }
@@ -5,8 +5,7 @@ use super::{
   format_href_to_discord,
   get_redis,
   parse,
-  save_to_redis,
-  task_err
+  save_to_redis
 };
 
 use {
@@ -22,7 +21,8 @@ use {
   std::{
     io::Cursor,
     sync::Arc
-  }
+  },
+  tracing::error
 };
 
 pub struct Esxi {
@@ -46,17 +46,13 @@ impl RSSFeed for Esxi {
     let redis = get_redis().await;
     let rkey = "RSS_ESXi";
 
-    let res = fetch_feed(self.url()).await?;
-    let data = res.text().await?;
+    let data = fetch_feed(self.url()).await?;
     let cursor = Cursor::new(data);
 
-    let feed = parse(cursor).map_err(|e| {
-      task_err("RSS:ESXi", &format!("Error parsing RSS feed: {e}"));
-      e
-    })?;
+    let feed = parse(cursor).map_err(|e| format!("Error parsing RSS feed: {e}"))?;
 
     if feed.entries.is_empty() {
-      task_err("RSS:ESXi", "No entries found in the feed!");
+      error!(feed = "ESXi", "No entries found in the feed!");
       return Ok(None);
     }
 
@@ -105,9 +101,9 @@ impl RSSFeed for Esxi {
         )))
       }
     } else {
-      task_err(
-        "RSS:ESXi",
-        &format!("Article term does not match the expected RegEx pattern! ({})", article.categories[3].term)
+      error!(
+        feed = "ESXi",
+        "Article term does not match the expected RegEx pattern! ({})", article.categories[3].term
       );
       Ok(None)
     }
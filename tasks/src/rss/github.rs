@@ -8,8 +8,6 @@ use super::{
   get_redis,
   parse,
   save_to_redis,
-  task_err,
-  task_info,
   trim_old_content
 };
 
@@ -24,6 +22,10 @@ use {
   std::{
     io::Cursor,
     sync::Arc
+  },
+  tracing::{
+    debug,
+    error
   }
 };
 
@@ -49,17 +51,13 @@ impl RSSFeed for GitHub {
     let rkey = "RSS_GitHub";
     let rkey_content = format!("{rkey}_Content");
 
-    let res = fetch_feed(self.url()).await?;
-    let data = res.text().await?;
+    let data = fetch_feed(self.url()).await?;
     let cursor = Cursor::new(data);
 
-    let feed = parse(cursor).map_err(|e| {
-      task_err("RSS:GitHub", &format!("Error parsing RSS feed: {e}"));
-      e
-    })?;
+    let feed = parse(cursor).map_err(|e| format!("Error parsing RSS feed: {e}"))?;
 
     if feed.entries.is_empty() {
-      task_err("RSS:GitHub", "No entries found in the feed!");
+      error!(feed = "GitHub", "No entries found in the feed!");
       return Ok(None);
     }
 
@@ -95,7 +93,7 @@ impl RSSFeed for GitHub {
       IncidentColorMap::Default.color()
     };
 
-    task_info("RSS:GitHub:Debug", &format!("Checking cache for incident ID: {}", &article.links[0].href));
+    debug!(feed = "GitHub", "Checking cache for incident ID: {}", &article.links[0].href);
     if cached_incident.is_empty() {
       save_to_redis(rkey, &get_incident_id(&article.links[0].href).unwrap()).await?;
       save_to_redis(&rkey_content, &new_content).await?;
@@ -110,7 +108,7 @@ impl RSSFeed for GitHub {
         } else {
           redis.set(&rkey_content, &new_content).await.unwrap();
           redis.expire(&rkey_content, 21600).await.unwrap();
-          task_info("RSS:GitHub:Debug", "Incident added in cache and preparing to send embed to Discord");
+          debug!(feed = "GitHub", "Incident added in cache and preparing to send embed to Discord");
 
           Ok(Some(RSSFeedOutput::IncidentEmbed(embed(
             color,
@@ -123,7 +121,7 @@ impl RSSFeed for GitHub {
       } else {
         save_to_redis(rkey, &incident).await?;
         redis.set(&rkey_content, &new_content).await.unwrap();
-        task_info("RSS:GitHub:Debug", "Incident updated in cache and preparing to send embed to Discord");
+        debug!(feed = "GitHub", "Incident updated in cache and preparing to send embed to Discord");
 
         Ok(Some(RSSFeedOutput::IncidentEmbed(embed(
           color,
@@ -134,9 +132,9 @@ impl RSSFeed for GitHub {
         ))))
       }
     } else {
-      task_err(
-        "RSS:GitHub",
-        &format!("Incident ID does not match the expected RegEx pattern! ({})", &article.links[0].href)
+      error!(
+        feed = "GitHub",
+        "Incident ID does not match the expected RegEx pattern! ({})", &article.links[0].href
       );
       Ok(None)
     }
@@ -8,7 +8,6 @@ use super::{
   get_redis,
   parse,
   save_to_redis,
-  task_err,
   trim_old_content
 };
 
@@ -23,7 +22,8 @@ use {
   std::{
     io::Cursor,
     sync::Arc
-  }
+  },
+  tracing::error
 };
 
 pub struct GPortal {
@@ -48,17 +48,13 @@ impl RSSFeed for GPortal {
     let rkey = "RSS_GPortal";
     let rkey_content = format!("{rkey}_Content");
 
-    let res = fetch_feed(self.url()).await?;
-    let data = res.text().await?;
+    let data = fetch_feed(self.url()).await?;
     let cursor = Cursor::new(data);
 
-    let feed = parse(cursor).map_err(|e| {
-      task_err("RSS:GPortal", &format!("Error parsing RSS feed: {e}"));
-      e
-    })?;
+    let feed = parse(cursor).map_err(|e| format!("Error parsing RSS feed: {e}"))?;
 
     if feed.entries.is_empty() {
-      task_err("RSS:GPortal", "No entries found in the feed!");
+      error!(feed = "GPortal", "No entries found in the feed!");
       return Ok(None);
     }
 
@@ -133,9 +129,9 @@ impl RSSFeed for GPortal {
         ))))
       }
     } else {
-      task_err(
-        "RSS:GPortal",
-        &format!("Incident ID does not match the expected RegEx pattern! ({})", &article.links[0].href)
+      error!(
+        feed = "GPortal",
+        "Incident ID does not match the expected RegEx pattern! ({})", &article.links[0].href
       );
       Ok(None)
     }
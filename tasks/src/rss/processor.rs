@@ -1,6 +1,7 @@
 use kon_libs::{
   BINARY_PROPERTIES,
-  KonResult
+  KonResult,
+  metrics::RSS_POLL_DURATION
 };
 
 use super::{
@@ -20,7 +21,9 @@ use {
     Http
   },
   regex::Regex,
-  std::sync::Arc
+  std::sync::Arc,
+  tokio::time::Instant,
+  tracing::error
 };
 
 //  This is for building up the embed with the feed data
@@ -33,7 +36,7 @@ async fn process_regular_embed(
   redis_key: &str
 ) -> KonResult<()> {
   let redis = get_redis().await;
-  let channel = ChannelId::new(BINARY_PROPERTIES.rss_channel);
+  let channel = ChannelId::new(BINARY_PROPERTIES.load().rss_channel);
 
   let msg_id_key: Option<String> = redis.get(redis_key).await?;
 
@@ -60,7 +63,7 @@ async fn process_incident_embed(
   content_key: &str
 ) -> KonResult<()> {
   let redis = get_redis().await;
-  let channel = ChannelId::new(BINARY_PROPERTIES.rss_channel);
+  let channel = ChannelId::new(BINARY_PROPERTIES.load().rss_channel);
 
   let msg_id_key: Option<String> = redis.get(redis_key).await?;
   let cached_content: Option<String> = redis.get(content_key).await.unwrap_or(None);
@@ -97,7 +100,7 @@ async fn process_msg_content(
   redis_key: &str
 ) -> KonResult<()> {
   let redis = get_redis().await;
-  let channel = ChannelId::new(BINARY_PROPERTIES.rss_channel);
+  let channel = ChannelId::new(BINARY_PROPERTIES.load().rss_channel);
 
   let msg_id_key: Option<String> = redis.get(redis_key).await?;
 
@@ -128,44 +131,83 @@ impl RSSProcessor {
     self.feeds.push(feed);
   }
 
+  fn find_feed(
+    &self,
+    name: &str
+  ) -> KonResult<&RSSFeedBox> {
+    self
+      .feeds
+      .iter()
+      .find(|feed| feed.name().eq_ignore_ascii_case(name))
+      .ok_or_else(|| format!("No feed named \"{name}\" is registered").into())
+  }
+
+  /// Process a single feed, a failure is logged and ends up in the logs channel through the log forwarder
+  async fn process_feed(
+    &self,
+    ctx: Arc<Context>,
+    feed: &RSSFeedBox
+  ) {
+    let feed_name = feed.name();
+    let redis_key = format!("RSS_{feed_name}_MsgId");
+
+    let started = Instant::now();
+    let result = match feed.process(ctx.clone()).await {
+      Ok(Some(output)) => match output {
+        RSSFeedOutput::RegularEmbed(embed) => process_regular_embed(&ctx.http, embed, &redis_key).await,
+        RSSFeedOutput::IncidentEmbed(embed) => process_incident_embed(&ctx.http, embed, &redis_key, &format!("RSS_{feed_name}_Content")).await,
+        RSSFeedOutput::Content(content) => process_msg_content(&ctx.http, content, &redis_key).await
+      },
+      Ok(None) => Ok(()),
+      Err(e) => Err(e)
+    };
+
+    RSS_POLL_DURATION
+      .with_label_values(&[feed_name, if result.is_ok() { "ok" } else { "error" }])
+      .observe(started.elapsed().as_secs_f64());
+
+    if let Err(e) = result {
+      error!(task = TASK_NAME, feed = feed_name, "Feed failed: {e}");
+    }
+  }
+
   pub async fn process_all(
     &self,
     ctx: Arc<Context>
   ) -> KonResult<()> {
-    let mut discord_msg: Vec<String> = Vec::new();
-
     for feed in &self.feeds {
-      let feed_name = feed.name();
-      let redis_key = format!("RSS_{feed_name}_MsgId");
-      let error_msg = format!("**[{TASK_NAME}:{feed_name}:Error]:** Feed failed with the following error:```\n{{ error }}\n```");
-
-      match feed.process(ctx.clone()).await {
-        Ok(Some(output)) => match output {
-          RSSFeedOutput::RegularEmbed(embed) => {
-            if let Err(e) = process_regular_embed(&ctx.http, embed, &redis_key).await {
-              discord_msg.push(error_msg.replace("{{ error }}", &e.to_string()))
-            }
-          },
-          RSSFeedOutput::IncidentEmbed(embed) => {
-            if let Err(e) = process_incident_embed(&ctx.http, embed, &redis_key, &format!("RSS_{feed_name}_Content")).await {
-              discord_msg.push(error_msg.replace("{{ error }}", &e.to_string()))
-            }
-          },
-          RSSFeedOutput::Content(content) => {
-            if let Err(e) = process_msg_content(&ctx.http, content, &redis_key).await {
-              discord_msg.push(error_msg.replace("{{ error }}", &e.to_string()))
-            }
-          },
-        },
-        Ok(None) => (),
-        Err(e) => discord_msg.push(error_msg.replace("{{ error }}", &e.to_string()))
-      }
+      self.process_feed(ctx.clone(), feed).await;
     }
 
-    if !discord_msg.is_empty() {
-      ChannelId::new(BINARY_PROPERTIES.kon_logs)
-        .send_message(&ctx.http, CreateMessage::new().content(discord_msg.join("\n")))
-        .await?;
+    Ok(())
+  }
+
+  /// Force a poll of one feed outside of the regular interval
+  pub async fn process_by_name(
+    &self,
+    ctx: Arc<Context>,
+    name: &str
+  ) -> KonResult<()> {
+    let feed = self.find_feed(name)?;
+    self.process_feed(ctx, feed).await;
+
+    Ok(())
+  }
+
+  /// Forget everything cached for a feed, the next poll will treat it as a fresh start
+  pub async fn clear_cache(
+    &self,
+    name: &str
+  ) -> KonResult<()> {
+    let feed_name = self.find_feed(name)?.name();
+    let redis = get_redis().await;
+
+    for key in [
+      format!("RSS_{feed_name}"),
+      format!("RSS_{feed_name}_Content"),
+      format!("RSS_{feed_name}_MsgId")
+    ] {
+      redis.del(&key).await?;
     }
 
     Ok(())
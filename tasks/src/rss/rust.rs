@@ -4,8 +4,7 @@ use super::{
   fetch_feed,
   get_redis,
   parse,
-  save_to_redis,
-  task_err
+  save_to_redis
 };
 
 use {
@@ -18,7 +17,8 @@ use {
   std::{
     io::Cursor,
     sync::Arc
-  }
+  },
+  tracing::error
 };
 
 pub struct RustBlog {
@@ -42,17 +42,13 @@ impl RSSFeed for RustBlog {
     let redis = get_redis().await;
     let rkey = "RSS_RustBlog";
 
-    let res = fetch_feed(self.url()).await?;
-    let data = res.text().await?;
+    let data = fetch_feed(self.url()).await?;
     let cursor = Cursor::new(data);
 
-    let feed = parse(cursor).map_err(|e| {
-      task_err("RSS:RustBlog", &format!("Error parsing RSS feed: {e}"));
-      e
-    })?;
+    let feed = parse(cursor).map_err(|e| format!("Error parsing RSS feed: {e}"))?;
 
     if feed.entries.is_empty() {
-      task_err("RSS:RustBlog", "No entries found in the feed!");
+      error!(feed = "RustBlog", "No entries found in the feed!");
       return Ok(None);
     }
 
@@ -84,10 +80,7 @@ impl RSSFeed for RustBlog {
         ))))
       }
     } else {
-      task_err(
-        "RSS:RustBlog",
-        &format!("Article URL does not match the expected RegEx pattern! ({article_id})")
-      );
+      error!(feed = "RustBlog", "Article URL does not match the expected RegEx pattern! ({article_id})");
       Ok(None)
     }
   }
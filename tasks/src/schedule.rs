@@ -0,0 +1,204 @@
+use {
+  chrono::{
+    DateTime,
+    Utc
+  },
+  kon_libs::KonResult,
+  rand::Rng,
+  std::str::FromStr,
+  tokio::time::Duration
+};
+
+/// What to do when the bot was down (or on standby) while a run was due
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissedRun {
+  /// Forget about it and wait for the next slot
+  Skip,
+  /// Run once straight away, then carry on with the schedule
+  CatchUp
+}
+
+enum ScheduleKind {
+  Cron(Box<cron::Schedule>),
+  Every { period: Duration, offset: Duration }
+}
+
+/// When a scheduled task should run, built with [`TaskSchedule::cron`] or [`TaskSchedule::every`]
+pub struct TaskSchedule {
+  kind:   ScheduleKind,
+  jitter: Duration,
+  missed: MissedRun
+}
+
+impl TaskSchedule {
+  /// Cron expression with a seconds field, e.g. `0 */5 * * * *`
+  pub fn cron(expression: &str) -> KonResult<Self> {
+    let schedule = cron::Schedule::from_str(expression).map_err(|e| format!("Invalid cron expression \"{expression}\": {e}"))?;
+    Ok(Self::new(ScheduleKind::Cron(Box::new(schedule))))
+  }
+
+  /// Run every `period`, aligned to the clock (every 5 mins runs at :00, :05, :10 and so on)
+  pub fn every(period: Duration) -> Self {
+    Self::new(ScheduleKind::Every {
+      period,
+      offset: Duration::ZERO
+    })
+  }
+
+  fn new(kind: ScheduleKind) -> Self {
+    Self {
+      kind,
+      jitter: Duration::ZERO,
+      missed: MissedRun::Skip
+    }
+  }
+
+  /// Shift an `every` schedule off the clock alignment, ignored for cron schedules
+  pub fn offset(
+    mut self,
+    offset: Duration
+  ) -> Self {
+    if let ScheduleKind::Every { offset: o, .. } = &mut self.kind {
+      *o = offset;
+    }
+    self
+  }
+
+  /// Delay each run by a random amount up to `jitter`
+  pub fn jitter(
+    mut self,
+    jitter: Duration
+  ) -> Self {
+    self.jitter = jitter;
+    self
+  }
+
+  pub fn missed(
+    mut self,
+    policy: MissedRun
+  ) -> Self {
+    self.missed = policy;
+    self
+  }
+
+  /// Next slot strictly after the given time, `None` if the schedule has no more slots
+  pub(crate) fn next_after(
+    &self,
+    after: DateTime<Utc>
+  ) -> Option<DateTime<Utc>> {
+    match &self.kind {
+      ScheduleKind::Cron(schedule) => schedule.after(&after).next(),
+      ScheduleKind::Every { period, offset } => {
+        let period = (period.as_millis() as i64).max(1);
+        let offset = offset.as_millis() as i64 % period;
+        let slot = (after.timestamp_millis() - offset).div_euclid(period) * period + period + offset;
+        DateTime::from_timestamp_millis(slot)
+      }
+    }
+  }
+
+  /// First slot to wait for after a (re)start, based on when the task last ran
+  pub(crate) fn first_run(
+    &self,
+    last_run: Option<DateTime<Utc>>,
+    now: DateTime<Utc>
+  ) -> Option<DateTime<Utc>> {
+    let Some(last_run) = last_run else {
+      // Never ran before, no point waiting a whole period
+      return Some(now);
+    };
+
+    match self.next_after(last_run) {
+      Some(due) if due <= now => match self.missed {
+        MissedRun::Skip => self.next_after(now),
+        MissedRun::CatchUp => Some(now)
+      },
+      due => due
+    }
+  }
+
+  pub(crate) fn jitter_delay(&self) -> Duration {
+    if self.jitter.is_zero() {
+      return Duration::ZERO;
+    }
+
+    Duration::from_millis(rand::thread_rng().gen_range(0..=self.jitter.as_millis() as u64))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    chrono::TimeZone
+  };
+
+  fn at(
+    hour: u32,
+    min: u32,
+    sec: u32
+  ) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 6, hour, min, sec).unwrap()
+  }
+
+  #[test]
+  fn cron_next_fire() {
+    let schedule = TaskSchedule::cron("0 */5 * * * *").unwrap();
+
+    assert_eq!(schedule.next_after(at(10, 2, 30)), Some(at(10, 5, 0)));
+    // Strictly after, a slot isn't its own next one
+    assert_eq!(schedule.next_after(at(10, 5, 0)), Some(at(10, 10, 0)));
+  }
+
+  #[test]
+  fn invalid_cron_expressions() {
+    for expression in ["", "every five minutes", "0 61 * * * *"] {
+      let Err(e) = TaskSchedule::cron(expression) else {
+        panic!("\"{expression}\" was accepted");
+      };
+      assert!(e.to_string().contains(&format!("\"{expression}\"")));
+    }
+  }
+
+  #[test]
+  fn every_aligns_to_the_clock() {
+    let schedule = TaskSchedule::every(Duration::from_secs(300));
+
+    assert_eq!(schedule.next_after(at(10, 2, 30)), Some(at(10, 5, 0)));
+    assert_eq!(schedule.next_after(at(10, 5, 0)), Some(at(10, 10, 0)));
+  }
+
+  #[test]
+  fn every_with_an_offset() {
+    let schedule = TaskSchedule::every(Duration::from_secs(300)).offset(Duration::from_secs(60));
+
+    assert_eq!(schedule.next_after(at(10, 0, 30)), Some(at(10, 1, 0)));
+    assert_eq!(schedule.next_after(at(10, 2, 30)), Some(at(10, 6, 0)));
+    // An offset past the period wraps around
+    let wrapped = TaskSchedule::every(Duration::from_secs(300)).offset(Duration::from_secs(360));
+    assert_eq!(wrapped.next_after(at(10, 2, 30)), Some(at(10, 6, 0)));
+  }
+
+  #[test]
+  fn first_run_without_a_previous_run() {
+    let schedule = TaskSchedule::every(Duration::from_secs(300));
+    assert_eq!(schedule.first_run(None, at(10, 2, 30)), Some(at(10, 2, 30)));
+  }
+
+  #[test]
+  fn first_run_waits_for_the_next_slot() {
+    let schedule = TaskSchedule::every(Duration::from_secs(300));
+    assert_eq!(schedule.first_run(Some(at(10, 0, 0)), at(10, 2, 30)), Some(at(10, 5, 0)));
+  }
+
+  #[test]
+  fn first_run_after_a_missed_slot() {
+    let (last_run, now) = (at(9, 0, 0), at(10, 2, 30));
+
+    let skip = TaskSchedule::every(Duration::from_secs(300));
+    assert_eq!(skip.first_run(Some(last_run), now), Some(at(10, 5, 0)));
+
+    let catch_up = TaskSchedule::every(Duration::from_secs(300)).missed(MissedRun::CatchUp);
+    assert_eq!(catch_up.first_run(Some(last_run), now), Some(now));
+  }
+}
@@ -0,0 +1,713 @@
+use super::{
+  TaskSchedule,
+  get_redis,
+  redis_if_connected
+};
+
+use {
+  chrono::{
+    DateTime,
+    Utc
+  },
+  futures::{
+    FutureExt,
+    future::{
+      BoxFuture,
+      join_all
+    }
+  },
+  kon_libs::{
+    KonError,
+    KonResult
+  },
+  kon_repo::LeaderLock,
+  poise::serenity_prelude::Context,
+  std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{
+      Arc,
+      LazyLock,
+      Mutex,
+      OnceLock
+    }
+  },
+  tokio::{
+    select,
+    sync::{
+      Notify,
+      watch
+    },
+    task::{
+      JoinHandle,
+      spawn
+    },
+    time::{
+      Duration,
+      Instant,
+      sleep,
+      timeout
+    }
+  },
+  tracing::{
+    error,
+    info,
+    warn
+  }
+};
+
+type TaskFn = Arc<dyn Fn(Arc<Context>) -> BoxFuture<'static, KonResult<()>> + Send + Sync>;
+
+/// How long a task's leader lock lives without being renewed
+const LOCK_TTL: Duration = Duration::from_secs(30);
+
+/// How often a standby instance checks if the lock is up for grabs
+const LOCK_RETRY: Duration = Duration::from_secs(10);
+
+/// Restart delay after a crash, doubles on every consecutive crash
+const BACKOFF_MIN: Duration = Duration::from_secs(5);
+const BACKOFF_MAX: Duration = Duration::from_secs(300);
+
+/// A task that stayed up this long gets its backoff reset on the next crash
+const HEALTHY_RUN: Duration = Duration::from_secs(600);
+
+/// How long tasks get to release their lock after being cut off at the shutdown deadline
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskState {
+  /// Registered but not started, or stopped on request
+  Stopped,
+  /// Another instance holds the leader lock
+  Standby,
+  Running,
+  /// Crashed and waiting to be restarted
+  Backoff,
+  /// Returned without an error, won't be restarted
+  Finished
+}
+
+impl fmt::Display for TaskState {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    let state = match self {
+      Self::Stopped => "Stopped",
+      Self::Standby => "Standby",
+      Self::Running => "Running",
+      Self::Backoff => "Backoff",
+      Self::Finished => "Finished"
+    };
+    write!(f, "{state}")
+  }
+}
+
+/// Bookkeeping shown by the `/tasks` command
+#[derive(Clone, Default)]
+pub struct TaskStats {
+  pub last_run:   Option<DateTime<Utc>>,
+  pub last_error: Option<String>,
+  pub next_run:   Option<DateTime<Utc>>,
+  pub run_count:  u64
+}
+
+/// Snapshot of a registered task
+#[derive(Clone)]
+pub struct TaskInfo {
+  pub name:      &'static str,
+  pub state:     TaskState,
+  pub paused:    bool,
+  pub scheduled: bool,
+  pub stats:     TaskStats
+}
+
+struct TaskEntry {
+  name:    &'static str,
+  task:    TaskFn,
+  state:   TaskState,
+  paused:  bool,
+  stats:   TaskStats,
+  stop:    Arc<Notify>,
+  /// Only scheduled tasks can be triggered outside of their schedule
+  trigger: Option<Arc<Notify>>,
+  handle:  Option<JoinHandle<()>>
+}
+
+enum RunOutcome {
+  Finished,
+  Failed(String),
+  LostLock,
+  Stopped
+}
+
+/// Named registry of background tasks, each one supervised and restarted on failure
+pub struct Scheduler {
+  ctx:      OnceLock<Arc<Context>>,
+  tasks:    Mutex<HashMap<&'static str, TaskEntry>>,
+  /// Flipped on shutdown, scheduled tasks finish their current run and don't start another one
+  draining: watch::Sender<bool>
+}
+
+static SCHEDULER: LazyLock<Scheduler> = LazyLock::new(|| Scheduler {
+  ctx:      OnceLock::new(),
+  tasks:    Mutex::new(HashMap::new()),
+  draining: watch::channel(false).0
+});
+
+pub fn scheduler() -> &'static Scheduler { &SCHEDULER }
+
+impl Scheduler {
+  /// Add a long-running task to the registry, does nothing if the name is already taken
+  pub fn register<F, T>(
+    &self,
+    name: &'static str,
+    task: F
+  ) where
+    F: Fn(Arc<Context>) -> T + Send + Sync + 'static,
+    T: Future<Output = KonResult<()>> + Send + 'static
+  {
+    self.insert(name, None, Arc::new(move |ctx: Arc<Context>| task(ctx).boxed()));
+  }
+
+  /// Add a task that runs once per slot of the given schedule, does nothing if the name is already taken
+  pub fn register_scheduled<F, T>(
+    &self,
+    name: &'static str,
+    schedule: TaskSchedule,
+    job: F
+  ) where
+    F: Fn(Arc<Context>) -> T + Send + Sync + 'static,
+    T: Future<Output = KonResult<()>> + Send + 'static
+  {
+    let schedule = Arc::new(schedule);
+    let trigger = Arc::new(Notify::new());
+    let job: TaskFn = Arc::new(move |ctx: Arc<Context>| job(ctx).boxed());
+
+    let task_trigger = trigger.clone();
+    self.insert(
+      name,
+      Some(trigger),
+      Arc::new(move |ctx: Arc<Context>| run_scheduled(name, schedule.clone(), job.clone(), task_trigger.clone(), ctx).boxed())
+    );
+  }
+
+  fn insert(
+    &self,
+    name: &'static str,
+    trigger: Option<Arc<Notify>>,
+    task: TaskFn
+  ) {
+    self.tasks.lock().unwrap().entry(name).or_insert_with(|| TaskEntry {
+      name,
+      task,
+      state: TaskState::Stopped,
+      paused: false,
+      stats: TaskStats::default(),
+      stop: Arc::new(Notify::new()),
+      trigger,
+      handle: None
+    });
+  }
+
+  /// Start every registered task that isn't already running or paused
+  pub fn start_all(
+    &self,
+    ctx: Arc<Context>
+  ) {
+    let _ = self.ctx.set(ctx);
+
+    let names: Vec<&'static str> = self
+      .tasks
+      .lock()
+      .unwrap()
+      .values()
+      .filter(|entry| !entry.paused)
+      .map(|entry| entry.name)
+      .collect();
+    for name in names {
+      if let Err(y) = self.start(name) {
+        error!(task = name, "Failed to start: {y}");
+      }
+    }
+  }
+
+  pub fn start(
+    &self,
+    name: &str
+  ) -> KonResult<()> {
+    let ctx = self.ctx.get().cloned().ok_or("Scheduler hasn't been given a context yet")?;
+    let mut tasks = self.tasks.lock().unwrap();
+    let entry = tasks.get_mut(name).ok_or_else(|| unknown_task(name))?;
+
+    if entry.handle.as_ref().is_some_and(|handle| !handle.is_finished()) {
+      return Ok(());
+    }
+
+    // Fresh notifier so a stale stop request can't kill the new run
+    entry.stop = Arc::new(Notify::new());
+    entry.handle = Some(spawn(supervise(entry.name, entry.task.clone(), entry.stop.clone(), ctx)));
+
+    Ok(())
+  }
+
+  /// Ask a task to stop and wait for it to wind down
+  pub async fn stop(
+    &self,
+    name: &str
+  ) -> KonResult<()> {
+    let handle = {
+      let mut tasks = self.tasks.lock().unwrap();
+      let entry = tasks.get_mut(name).ok_or_else(|| unknown_task(name))?;
+      entry.stop.notify_one();
+      entry.handle.take()
+    };
+
+    if let Some(handle) = handle {
+      handle.await?;
+    }
+
+    Ok(())
+  }
+
+  pub async fn restart(
+    &self,
+    name: &str
+  ) -> KonResult<()> {
+    self.stop(name).await?;
+    self.start(name)
+  }
+
+  /// Run a scheduled task right away instead of waiting for its next slot
+  pub fn trigger(
+    &self,
+    name: &str
+  ) -> KonResult<()> {
+    let tasks = self.tasks.lock().unwrap();
+    let entry = tasks.get(name).ok_or_else(|| unknown_task(name))?;
+    let trigger = entry
+      .trigger
+      .as_ref()
+      .ok_or_else(|| format!("Task \"{name}\" doesn't run on a schedule"))?;
+
+    trigger.notify_one();
+    Ok(())
+  }
+
+  /// Scheduled tasks keep their slot but skip runs while paused, long-running tasks get stopped
+  pub async fn pause(
+    &self,
+    name: &str
+  ) -> KonResult<()> {
+    let scheduled = {
+      let mut tasks = self.tasks.lock().unwrap();
+      let entry = tasks.get_mut(name).ok_or_else(|| unknown_task(name))?;
+      entry.paused = true;
+      entry.trigger.is_some()
+    };
+
+    if !scheduled {
+      self.stop(name).await?;
+    }
+
+    Ok(())
+  }
+
+  pub fn resume(
+    &self,
+    name: &str
+  ) -> KonResult<()> {
+    let scheduled = {
+      let mut tasks = self.tasks.lock().unwrap();
+      let entry = tasks.get_mut(name).ok_or_else(|| unknown_task(name))?;
+      entry.paused = false;
+      entry.trigger.is_some()
+    };
+
+    if !scheduled {
+      self.start(name)?;
+    }
+
+    Ok(())
+  }
+
+  /// Let scheduled tasks finish their current run and stop everything else,
+  /// whatever is still running once the deadline passes gets stopped mid-run
+  pub async fn shutdown(
+    &self,
+    deadline: Duration
+  ) {
+    self.draining.send_replace(true);
+
+    let mut handles: Vec<(&'static str, JoinHandle<()>)> = {
+      let mut tasks = self.tasks.lock().unwrap();
+      tasks
+        .values_mut()
+        .filter_map(|entry| {
+          // Long-running tasks have no natural end, so there's nothing to wait for
+          if entry.trigger.is_none() {
+            entry.stop.notify_one();
+          }
+          entry.handle.take().map(|handle| (entry.name, handle))
+        })
+        .collect()
+    };
+
+    if timeout(deadline, join_all(handles.iter_mut().map(|(_, handle)| handle))).await.is_ok() {
+      return;
+    }
+
+    for (name, handle) in &handles {
+      if handle.is_finished() {
+        continue;
+      }
+
+      warn!(task = name, "Didn't finish before the shutdown deadline, stopping it");
+      if let Some(entry) = self.tasks.lock().unwrap().get(name) {
+        entry.stop.notify_one();
+      }
+    }
+
+    for (name, handle) in handles {
+      if timeout(STOP_GRACE, handle).await.is_err() {
+        warn!(task = name, "Still hasn't stopped, its leader lock will have to expire");
+      }
+    }
+  }
+
+  /// Write the scheduled tasks' last runs back to Redis once they've stopped, so a run whose own
+  /// write failed isn't repeated after a restart. Never moves a newer run from another instance back.
+  pub async fn persist(&self) {
+    // Nothing was ever connected, so nothing ran either
+    let Some(redis) = redis_if_connected() else {
+      return;
+    };
+
+    let last_runs: Vec<(&'static str, DateTime<Utc>)> = self
+      .tasks
+      .lock()
+      .unwrap()
+      .values()
+      .filter(|entry| entry.trigger.is_some())
+      .filter_map(|entry| entry.stats.last_run.map(|last_run| (entry.name, last_run)))
+      .collect();
+
+    for (name, last_run) in last_runs {
+      let key = last_run_key(name);
+      let stored = match redis.get(&key).await {
+        Ok(stored) => stored.and_then(|ts| ts.parse::<i64>().ok()),
+        Err(y) => {
+          error!(task = name, "Failed to read the saved last run: {y}");
+          continue;
+        }
+      };
+
+      if stored.is_some_and(|stored| stored >= last_run.timestamp()) {
+        continue;
+      }
+
+      if let Err(y) = redis.set(&key, &last_run.timestamp().to_string()).await {
+        error!(task = name, "Failed to save the last run: {y}");
+      }
+    }
+  }
+
+  fn is_draining(&self) -> bool { *self.draining.borrow() }
+
+  pub fn state(
+    &self,
+    name: &str
+  ) -> Option<TaskState> {
+    self.tasks.lock().unwrap().get(name).map(|entry| entry.state)
+  }
+
+  /// Snapshot of every registered task, sorted by name
+  pub fn tasks(&self) -> Vec<TaskInfo> {
+    let mut tasks: Vec<TaskInfo> = self
+      .tasks
+      .lock()
+      .unwrap()
+      .values()
+      .map(|entry| TaskInfo {
+        name:      entry.name,
+        state:     entry.state,
+        paused:    entry.paused,
+        scheduled: entry.trigger.is_some(),
+        stats:     entry.stats.clone()
+      })
+      .collect();
+
+    tasks.sort_by_key(|task| task.name);
+    tasks
+  }
+
+  fn is_scheduled(
+    &self,
+    name: &str
+  ) -> bool {
+    self.tasks.lock().unwrap().get(name).is_some_and(|entry| entry.trigger.is_some())
+  }
+
+  fn is_paused(
+    &self,
+    name: &str
+  ) -> bool {
+    self.tasks.lock().unwrap().get(name).is_some_and(|entry| entry.paused)
+  }
+
+  fn set_state(
+    &self,
+    name: &str,
+    state: TaskState
+  ) {
+    if let Some(entry) = self.tasks.lock().unwrap().get_mut(name) {
+      entry.state = state;
+    }
+  }
+
+  fn record(
+    &self,
+    name: &str,
+    update: impl FnOnce(&mut TaskStats)
+  ) {
+    if let Some(entry) = self.tasks.lock().unwrap().get_mut(name) {
+      update(&mut entry.stats);
+    }
+  }
+}
+
+fn unknown_task(name: &str) -> KonError { format!("No task named \"{name}\" is registered").into() }
+
+fn panic_reason(panic: Box<dyn Any + Send>) -> String {
+  if let Some(reason) = panic.downcast_ref::<&str>() {
+    format!("panicked: {reason}")
+  } else if let Some(reason) = panic.downcast_ref::<String>() {
+    format!("panicked: {reason}")
+  } else {
+    "panicked".to_string()
+  }
+}
+
+fn last_run_key(name: &str) -> String { format!("Task_{name}_LastRun") }
+
+/// Loop driving a scheduled task, each run's errors and panics are logged and the schedule carries on
+async fn run_scheduled(
+  name: &'static str,
+  schedule: Arc<TaskSchedule>,
+  job: TaskFn,
+  trigger: Arc<Notify>,
+  ctx: Arc<Context>
+) -> KonResult<()> {
+  let redis = get_redis().await;
+  let last_run = redis
+    .get(&last_run_key(name))
+    .await?
+    .and_then(|ts| ts.parse::<i64>().ok())
+    .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0));
+
+  let mut next_run = schedule.first_run(last_run, Utc::now());
+  let mut draining = SCHEDULER.draining.subscribe();
+  SCHEDULER.record(name, |stats| stats.last_run = last_run);
+
+  loop {
+    SCHEDULER.record(name, |stats| stats.next_run = next_run);
+
+    if SCHEDULER.is_draining() {
+      info!(task = name, "Shutting down, not scheduling any more runs");
+      return Ok(());
+    }
+
+    let triggered = match next_run {
+      Some(next) => {
+        let delay = (next - Utc::now()).to_std().unwrap_or_default() + schedule.jitter_delay();
+        select! {
+          _ = sleep(delay) => false,
+          _ = trigger.notified() => true,
+          _ = draining.changed() => continue
+        }
+      },
+      // Schedule ran out of slots, only a manual trigger can run it now
+      None => select! {
+        _ = trigger.notified() => true,
+        _ = draining.changed() => continue
+      }
+    };
+
+    let started = Utc::now();
+
+    if triggered {
+      info!(task = name, "Triggered manually");
+    } else if SCHEDULER.is_paused(name) {
+      info!(task = name, "Paused, skipping this run");
+      next_run = schedule.next_after(started);
+      continue;
+    }
+
+    let error = match AssertUnwindSafe(job(ctx.clone())).catch_unwind().await {
+      Ok(Ok(())) => None,
+      Ok(Err(y)) => Some(y.to_string()),
+      Err(panic) => Some(panic_reason(panic))
+    };
+
+    if let Some(error) = &error {
+      error!(task = name, "Run failed: {error}");
+    }
+
+    SCHEDULER.record(name, |stats| {
+      stats.last_run = Some(started);
+      stats.run_count += 1;
+      if error.is_some() {
+        stats.last_error = error;
+      }
+    });
+
+    redis.set(&last_run_key(name), &started.timestamp().to_string()).await?;
+    next_run = schedule.next_after(started);
+  }
+}
+
+/// Sleep for the duration, returns `false` if a stop was requested or a shutdown started in the meantime.
+/// Shutdown only stops long-running tasks directly, this is what gets the others out of standby and backoff.
+async fn wait_or_stop(
+  stop: &Notify,
+  duration: Duration
+) -> bool {
+  let mut draining = SCHEDULER.draining.subscribe();
+
+  select! {
+    _ = sleep(duration) => true,
+    _ = stop.notified() => false,
+    _ = draining.wait_for(|draining| *draining) => false
+  }
+}
+
+/// Keeps renewing the lock and only returns once it has been lost
+async fn hold_lock(
+  name: &str,
+  lock: &LeaderLock
+) {
+  let mut last_renewed = Instant::now();
+
+  loop {
+    sleep(lock.ttl() / 3).await;
+
+    match lock.renew().await {
+      Ok(true) => last_renewed = Instant::now(),
+      Ok(false) => {
+        warn!(task = name, "Leader lock was taken over by another instance");
+        return;
+      },
+      Err(y) => {
+        error!(task = name, "Failed to renew leader lock: {y}");
+        if last_renewed.elapsed() >= lock.ttl() {
+          warn!(task = name, "Leader lock has expired, stepping down");
+          return;
+        }
+      }
+    }
+  }
+}
+
+async fn release_lock(
+  name: &str,
+  lock: &LeaderLock
+) {
+  if let Err(y) = lock.release().await {
+    error!(task = name, "Failed to release leader lock: {y}");
+  }
+}
+
+/// Runs a task whenever this instance holds its leader lock and restarts it with backoff after it errors or panics
+async fn supervise(
+  name: &'static str,
+  task: TaskFn,
+  stop: Arc<Notify>,
+  ctx: Arc<Context>
+) {
+  let lock = LeaderLock::new(get_redis().await, name, LOCK_TTL);
+  let mut backoff = BACKOFF_MIN;
+  let mut standby_noticed = false;
+
+  loop {
+    match lock.acquire().await {
+      Ok(true) => {
+        info!(task = name, "Acquired leader lock, starting task");
+        SCHEDULER.set_state(name, TaskState::Running);
+        standby_noticed = false;
+        let started = Instant::now();
+        if !SCHEDULER.is_scheduled(name) {
+          SCHEDULER.record(name, |stats| {
+            stats.last_run = Some(Utc::now());
+            stats.run_count += 1;
+          });
+        }
+
+        let outcome = select! {
+          result = AssertUnwindSafe(task(ctx.clone())).catch_unwind() => match result {
+            Ok(Ok(())) => RunOutcome::Finished,
+            Ok(Err(y)) => match y.source() {
+              Some(source) => RunOutcome::Failed(format!("{y} (caused by: {source})")),
+              None => RunOutcome::Failed(y.to_string())
+            },
+            Err(panic) => RunOutcome::Failed(panic_reason(panic))
+          },
+          _ = hold_lock(name, &lock) => RunOutcome::LostLock,
+          _ = stop.notified() => RunOutcome::Stopped
+        };
+
+        match outcome {
+          RunOutcome::Finished => {
+            release_lock(name, &lock).await;
+            info!(task = name, "Task finished");
+            SCHEDULER.set_state(name, TaskState::Finished);
+            return;
+          },
+          RunOutcome::Stopped => {
+            release_lock(name, &lock).await;
+            info!(task = name, "Task stopped");
+            SCHEDULER.set_state(name, TaskState::Stopped);
+            return;
+          },
+          RunOutcome::LostLock => {
+            info!(task = name, "Lost leader lock, task stopped and going back to standby");
+            SCHEDULER.set_state(name, TaskState::Standby);
+          },
+          RunOutcome::Failed(reason) => {
+            release_lock(name, &lock).await;
+            if started.elapsed() >= HEALTHY_RUN {
+              backoff = BACKOFF_MIN;
+            }
+
+            error!(task = name, "Task failed, restarting in {}s: {reason}", backoff.as_secs());
+            SCHEDULER.set_state(name, TaskState::Backoff);
+            SCHEDULER.record(name, |stats| stats.last_error = Some(reason));
+
+            if !wait_or_stop(&stop, backoff).await {
+              SCHEDULER.set_state(name, TaskState::Stopped);
+              return;
+            }
+
+            backoff = (backoff * 2).min(BACKOFF_MAX);
+            continue;
+          }
+        }
+      },
+      Ok(false) => {
+        if !standby_noticed {
+          info!(task = name, "Another instance holds the leader lock, waiting on standby");
+          SCHEDULER.set_state(name, TaskState::Standby);
+          standby_noticed = true;
+        }
+      },
+      Err(y) => error!(task = name, "Failed to acquire leader lock: {y}")
+    }
+
+    if !wait_or_stop(&stop, LOCK_RETRY).await {
+      SCHEDULER.set_state(name, TaskState::Stopped);
+      return;
+    }
+  }
+}
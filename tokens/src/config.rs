@@ -0,0 +1,110 @@
+use {
+  crate::{
+    EnvProvider,
+    FileProvider,
+    SecretsProvider
+  },
+  serde::Deserialize,
+  std::{
+    fmt,
+    path::PathBuf,
+    str::FromStr
+  }
+};
+
+#[cfg(feature = "tokenservice")]
+use crate::TokenServiceProvider;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsSource {
+  /// The private token service, needs the `tokenservice` feature
+  TokenService,
+  /// `KON_SECRET_*` environment variables
+  Env,
+  /// A TOML file, optionally encrypted
+  File
+}
+
+impl fmt::Display for SecretsSource {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>
+  ) -> fmt::Result {
+    match self {
+      Self::TokenService => write!(f, "tokenservice"),
+      Self::Env => write!(f, "env"),
+      Self::File => write!(f, "file")
+    }
+  }
+}
+
+impl FromStr for SecretsSource {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_lowercase().as_str() {
+      "tokenservice" => Ok(Self::TokenService),
+      "env" => Ok(Self::Env),
+      "file" => Ok(Self::File),
+      other => Err(format!("unknown secrets provider \"{other}\", expected tokenservice, env or file"))
+    }
+  }
+}
+
+/// The `[secrets]` section of the bot's config
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecretsConfig {
+  pub provider:  SecretsSource,
+  /// Service name to ask the token service for
+  pub service:   String,
+  /// Secrets file used by the `file` provider
+  pub path:      PathBuf,
+  /// Whether the secrets file is sealed with the key in `KON_SECRETS_KEY`
+  pub encrypted: bool,
+  /// Seconds the fetched secrets are cached for before they're fetched again
+  pub ttl:       u64
+}
+
+impl Default for SecretsConfig {
+  fn default() -> Self {
+    Self {
+      provider:  if cfg!(feature = "tokenservice") {
+        SecretsSource::TokenService
+      } else {
+        SecretsSource::Env
+      },
+      service:   "kon".to_string(),
+      path:      PathBuf::from("secrets.toml"),
+      encrypted: false,
+      ttl:       900
+    }
+  }
+}
+
+impl SecretsConfig {
+  /// Returns the offending key and the reason if the section can't be used
+  pub fn validate(&self) -> Result<(), (&'static str, String)> {
+    match self.provider {
+      SecretsSource::TokenService if !cfg!(feature = "tokenservice") => {
+        Err(("provider", "the tokenservice provider isn't available in this build".to_string()))
+      },
+      SecretsSource::TokenService if self.service.trim().is_empty() => Err(("service", "must not be empty".to_string())),
+      SecretsSource::File if self.path.as_os_str().is_empty() => Err(("path", "must not be empty".to_string())),
+      _ if self.ttl < 10 => Err(("ttl", "must be at least 10 seconds".to_string())),
+      _ => Ok(())
+    }
+  }
+
+  pub(crate) fn provider(&self) -> Box<dyn SecretsProvider> {
+    match self.provider {
+      #[cfg(feature = "tokenservice")]
+      SecretsSource::TokenService => Box::new(TokenServiceProvider::new(&self.service)),
+      #[cfg(not(feature = "tokenservice"))]
+      SecretsSource::TokenService => panic!("Secrets[Error]: kon_tokens was built without the tokenservice feature"),
+      SecretsSource::Env => Box::new(EnvProvider),
+      SecretsSource::File => Box::new(FileProvider::new(self.path.clone(), self.encrypted))
+    }
+  }
+}
@@ -1,42 +1,182 @@
+mod config;
+pub use config::{
+  SecretsConfig,
+  SecretsSource
+};
+
+mod store;
+use store::SecretsStore;
+
+mod providers;
+#[cfg(feature = "tokenservice")]
+pub use providers::TokenServiceProvider;
+pub use providers::{
+  EnvProvider,
+  FileProvider,
+  SECRETS_KEY_VAR,
+  parse_key,
+  seal
+};
+
 use {
+  async_trait::async_trait,
+  serde::Deserialize,
   std::{
-    env::args,
+    collections::BTreeMap,
     error::Error,
-    sync::LazyLock
+    sync::{
+      Arc,
+      OnceLock,
+      atomic::{
+        AtomicBool,
+        Ordering
+      }
+    }
   },
-  tokenservice_client::{
-    TokenService,
-    TokenServiceApi
+  tokio::{
+    sync::watch,
+    time::sleep
   },
-  tokio::sync::Mutex
+  tracing::error
 };
 
-static TSCLIENT: LazyLock<Mutex<TSClient>> = LazyLock::new(|| Mutex::new(TSClient::default()));
+pub type SecretsResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+static STORE: OnceLock<SecretsStore> = OnceLock::new();
+
+static REFRESHING: AtomicBool = AtomicBool::new(false);
 
-pub struct TSClient(TokenService);
+/// Credentials the bot needs to run, only `main` and `redis_uri` are required
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Secrets {
+  pub main:      String,
+  pub redis_uri: String,
+  #[serde(default)]
+  pub ilo_ip:    String,
+  #[serde(default)]
+  pub ilo_user:  String,
+  #[serde(default)]
+  pub ilo_pw:    String,
+  #[serde(default)]
+  pub wg_pms:    String,
+  /// Named iLO logins, hosts in the config refer to them by key
+  #[serde(default)]
+  pub ilo:       BTreeMap<String, IloCredentials>
+}
 
-impl Default for TSClient {
-  fn default() -> Self { Self::new() }
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct IloCredentials {
+  pub user:     String,
+  pub password: String
 }
 
-impl TSClient {
-  pub fn new() -> Self {
-    let args: Vec<String> = args().collect();
-    let service = if args.len() > 1 { &args[1] } else { "kon" };
-    Self(TokenService::new(service))
+impl Secrets {
+  /// Login for an iLO host, `default` falls back to `ilo_user` and `ilo_pw`.
+  ///
+  /// Names are matched case-insensitively since the env provider can only hand them over lowercased.
+  pub fn ilo_credentials(
+    &self,
+    name: &str
+  ) -> Option<IloCredentials> {
+    let named = self.ilo.get(name).or_else(|| {
+      self
+        .ilo
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, credentials)| credentials)
+    });
+    if let Some(credentials) = named {
+      return Some(credentials.clone());
+    }
+
+    (name.eq_ignore_ascii_case("default") && !self.ilo_user.is_empty()).then(|| IloCredentials {
+      user:     self.ilo_user.clone(),
+      password: self.ilo_pw.clone()
+    })
   }
+}
+
+/// Somewhere the [`Secrets`] can be fetched from
+#[async_trait]
+pub trait SecretsProvider: Send + Sync {
+  /// Short name used in log and error messages
+  fn name(&self) -> &'static str;
+
+  async fn fetch(&self) -> SecretsResult<Secrets>;
+}
+
+/// Pick the provider used by [`token_path`], has to be called before anything fetches secrets.
+///
+/// Returns false if a provider was already set.
+pub fn init_secrets(config: &SecretsConfig) -> bool { STORE.set(SecretsStore::new(config)).is_ok() }
 
-  pub async fn get(&self) -> Result<TokenServiceApi, Box<dyn Error + Send + Sync>> {
-    match self.0.connect().await {
-      Ok(a) => Ok(a),
-      Err(e) => Err(e)
+fn store() -> &'static SecretsStore { STORE.get_or_init(|| SecretsStore::new(&SecretsConfig::default())) }
+
+/// Get the cached secrets, they're only fetched from the provider once the cache has expired.
+///
+/// Panics if the provider fails and nothing has been cached yet.
+pub async fn token_path() -> Secrets {
+  let store = store();
+  match store.get().await {
+    Ok(a) => (*a).clone(),
+    Err(e) => panic!("Secrets[{}][Error]: {e}", store.provider_name())
+  }
+}
+
+/// Get notified whenever the fetched secrets differ from the previous ones,
+/// e.g. to reconnect with a rotated password
+pub fn subscribe_secrets() -> watch::Receiver<Option<Arc<Secrets>>> { store().subscribe() }
+
+/// Expire the cache so the next [`token_path`] call fetches from the provider,
+/// for when a credential has been rejected and might have been rotated
+pub fn invalidate_secrets() { store().invalidate() }
+
+/// Keep the cache warm by fetching the secrets again shortly before they expire
+pub async fn refresh_secrets() {
+  if REFRESHING.swap(true, Ordering::SeqCst) {
+    return;
+  }
+
+  let store = store();
+  loop {
+    sleep(store.ttl().mul_f32(0.8)).await;
+
+    if let Err(e) = store.refresh().await {
+      error!(provider = store.provider_name(), "Background refresh failed: {e}");
     }
   }
 }
 
-pub async fn token_path() -> TokenServiceApi {
-  match TSCLIENT.lock().await.get().await {
-    Ok(a) => a,
-    Err(e) => panic!("TSClient[Error]: {e}")
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn secrets() -> Secrets {
+    Secrets {
+      main:      String::new(),
+      redis_uri: String::new(),
+      ilo_ip:    String::new(),
+      ilo_user:  "admin".to_string(),
+      ilo_pw:    "admin".to_string(),
+      wg_pms:    String::new(),
+      ilo:       BTreeMap::from([(
+        "bubble".to_string(),
+        IloCredentials {
+          user:     "kon".to_string(),
+          password: "hunter2".to_string()
+        }
+      )])
+    }
+  }
+
+  #[test]
+  fn ilo_credentials_ignore_case() {
+    let secrets = secrets();
+
+    for name in ["bubble", "BUBBLE", "Bubble"] {
+      assert_eq!(secrets.ilo_credentials(name).map(|c| c.user).as_deref(), Some("kon"), "{name}");
+    }
+    assert_eq!(secrets.ilo_credentials("DEFAULT").map(|c| c.user).as_deref(), Some("admin"));
+    assert!(secrets.ilo_credentials("r640").is_none());
   }
 }
@@ -0,0 +1,15 @@
+mod env;
+pub use env::EnvProvider;
+
+mod file;
+pub use file::{
+  FileProvider,
+  SECRETS_KEY_VAR,
+  parse_key,
+  seal
+};
+
+#[cfg(feature = "tokenservice")]
+mod service;
+#[cfg(feature = "tokenservice")]
+pub use service::TokenServiceProvider;
@@ -0,0 +1,57 @@
+use {
+  crate::{
+    IloCredentials,
+    Secrets,
+    SecretsProvider,
+    SecretsResult
+  },
+  async_trait::async_trait,
+  std::{
+    collections::BTreeMap,
+    env::{
+      var,
+      vars
+    }
+  }
+};
+
+/// Reads the secrets from `KON_SECRET_*` environment variables,
+/// e.g. `KON_SECRET_MAIN` and `KON_SECRET_REDIS_URI`.
+///
+/// Named iLO logins are read from `KON_SECRET_ILO_<NAME>_USER` and `KON_SECRET_ILO_<NAME>_PW`,
+/// the name is lowercased and looked up regardless of case.
+pub struct EnvProvider;
+
+fn required(key: &str) -> SecretsResult<String> { var(key).map_err(|_| format!("{key} is not set").into()) }
+
+fn optional(key: &str) -> String { var(key).unwrap_or_default() }
+
+fn ilo_logins() -> BTreeMap<String, IloCredentials> {
+  vars()
+    .filter_map(|(key, user)| {
+      let name = key
+        .strip_prefix("KON_SECRET_ILO_")?
+        .strip_suffix("_USER")
+        .filter(|name| !name.is_empty())?;
+      let password = var(format!("KON_SECRET_ILO_{name}_PW")).ok()?;
+      Some((name.to_lowercase(), IloCredentials { user, password }))
+    })
+    .collect()
+}
+
+#[async_trait]
+impl SecretsProvider for EnvProvider {
+  fn name(&self) -> &'static str { "Env" }
+
+  async fn fetch(&self) -> SecretsResult<Secrets> {
+    Ok(Secrets {
+      main:      required("KON_SECRET_MAIN")?,
+      redis_uri: required("KON_SECRET_REDIS_URI")?,
+      ilo_ip:    optional("KON_SECRET_ILO_IP"),
+      ilo_user:  optional("KON_SECRET_ILO_USER"),
+      ilo_pw:    optional("KON_SECRET_ILO_PW"),
+      wg_pms:    optional("KON_SECRET_WG_PMS"),
+      ilo:       ilo_logins()
+    })
+  }
+}
@@ -0,0 +1,151 @@
+use {
+  crate::{
+    Secrets,
+    SecretsProvider,
+    SecretsResult
+  },
+  async_trait::async_trait,
+  chacha20poly1305::{
+    AeadCore,
+    ChaCha20Poly1305,
+    Key,
+    KeyInit,
+    Nonce,
+    aead::{
+      Aead,
+      OsRng
+    }
+  },
+  std::{
+    env::var,
+    path::PathBuf
+  },
+  tokio::fs::read
+};
+
+/// Environment variable holding the hex-encoded key for encrypted secrets files
+pub const SECRETS_KEY_VAR: &str = "KON_SECRETS_KEY";
+
+const NONCE_LEN: usize = 12;
+
+/// Reads the secrets from a TOML file with the same keys as [`Secrets`].
+///
+/// Encrypted files are ChaCha20-Poly1305 sealed (nonce followed by the ciphertext),
+/// `konctl seal-secrets` produces them from a plain file.
+pub struct FileProvider {
+  path:      PathBuf,
+  encrypted: bool
+}
+
+impl FileProvider {
+  pub fn new(
+    path: PathBuf,
+    encrypted: bool
+  ) -> Self {
+    Self { path, encrypted }
+  }
+}
+
+/// Parse a 32-byte key given as 64 hex characters
+pub fn parse_key(hex: &str) -> SecretsResult<Key> {
+  let hex = hex.trim();
+  // `from_str_radix` alone would let a sign through
+  if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+    return Err("key must be 64 hex characters".into());
+  }
+
+  let bytes = (0..hex.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+    .collect::<Result<Vec<u8>, _>>()
+    .map_err(|e| format!("key is not valid hex: {e}"))?;
+
+  Ok(*Key::from_slice(&bytes))
+}
+
+/// Encrypt a plain secrets file so it can be read with `encrypted = true`
+pub fn seal(
+  plaintext: &[u8],
+  key: &Key
+) -> SecretsResult<Vec<u8>> {
+  let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+  let ciphertext = ChaCha20Poly1305::new(key)
+    .encrypt(&nonce, plaintext)
+    .map_err(|_| "couldn't encrypt the secrets")?;
+
+  let mut sealed = nonce.to_vec();
+  sealed.extend(ciphertext);
+  Ok(sealed)
+}
+
+fn open(
+  sealed: &[u8],
+  key: &Key
+) -> SecretsResult<Vec<u8>> {
+  if sealed.len() < NONCE_LEN {
+    return Err("encrypted secrets file is truncated".into());
+  }
+
+  let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+  Ok(
+    ChaCha20Poly1305::new(key)
+      .decrypt(Nonce::from_slice(nonce), ciphertext)
+      .map_err(|_| "couldn't decrypt the secrets file, is the key right?")?
+  )
+}
+
+#[async_trait]
+impl SecretsProvider for FileProvider {
+  fn name(&self) -> &'static str { "File" }
+
+  async fn fetch(&self) -> SecretsResult<Secrets> {
+    let mut content = read(&self.path)
+      .await
+      .map_err(|e| format!("couldn't read \"{}\": {e}", self.path.display()))?;
+
+    if self.encrypted {
+      let key = var(SECRETS_KEY_VAR).map_err(|_| format!("{SECRETS_KEY_VAR} is not set"))?;
+      content = open(&content, &parse_key(&key)?)?;
+    }
+
+    let content = String::from_utf8(content).map_err(|_| format!("\"{}\" is not valid UTF-8", self.path.display()))?;
+    Ok(toml::from_str(&content).map_err(|e| format!("couldn't parse \"{}\": {e}", self.path.display()))?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+  #[test]
+  fn seal_and_open() {
+    let key = parse_key(KEY).unwrap();
+    let sealed = seal(b"main = \"token\"", &key).unwrap();
+
+    assert_ne!(&sealed[NONCE_LEN..], b"main = \"token\"");
+    assert_eq!(open(&sealed, &key).unwrap(), b"main = \"token\"");
+  }
+
+  #[test]
+  fn open_with_the_wrong_key() {
+    let sealed = seal(b"main = \"token\"", &parse_key(KEY).unwrap()).unwrap();
+    let wrong = parse_key(&KEY.replace("00", "ff")).unwrap();
+
+    assert!(open(&sealed, &wrong).is_err());
+    assert!(open(&sealed[..NONCE_LEN - 1], &wrong).is_err());
+  }
+
+  #[test]
+  fn malformed_keys() {
+    let signed = format!("+f{}", &KEY[2..]);
+    let short = &KEY[..62];
+    let not_hex = KEY.replace('a', "g");
+
+    for key in [signed.as_str(), short, not_hex.as_str(), "", "é"] {
+      assert!(parse_key(key).is_err(), "\"{key}\" was accepted");
+    }
+    assert!(parse_key(&format!(" {KEY}\n")).is_ok());
+  }
+}
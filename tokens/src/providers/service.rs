@@ -0,0 +1,36 @@
+use {
+  crate::{
+    Secrets,
+    SecretsProvider,
+    SecretsResult
+  },
+  async_trait::async_trait,
+  std::collections::BTreeMap,
+  tokenservice_client::TokenService
+};
+
+/// Fetches the secrets from the private token service
+pub struct TokenServiceProvider(TokenService);
+
+impl TokenServiceProvider {
+  pub fn new(service: &str) -> Self { Self(TokenService::new(service)) }
+}
+
+#[async_trait]
+impl SecretsProvider for TokenServiceProvider {
+  fn name(&self) -> &'static str { "TokenService" }
+
+  async fn fetch(&self) -> SecretsResult<Secrets> {
+    let api = self.0.connect().await?;
+    Ok(Secrets {
+      main:      api.main,
+      redis_uri: api.redis_uri,
+      ilo_ip:    api.ilo_ip,
+      ilo_user:  api.ilo_user,
+      ilo_pw:    api.ilo_pw,
+      wg_pms:    api.wg_pms,
+      // The token service only knows the one login
+      ilo:       BTreeMap::new()
+    })
+  }
+}
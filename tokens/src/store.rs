@@ -0,0 +1,130 @@
+use {
+  crate::{
+    Secrets,
+    SecretsConfig,
+    SecretsProvider,
+    SecretsResult
+  },
+  std::sync::{
+    Arc,
+    RwLock
+  },
+  tokio::{
+    sync::{
+      Mutex,
+      watch
+    },
+    time::{
+      Duration,
+      Instant
+    }
+  },
+  tracing::{
+    info,
+    warn
+  }
+};
+
+struct Cached {
+  expires: Instant,
+  secrets: Arc<Secrets>
+}
+
+/// Caches the provider's secrets and broadcasts them when they change
+pub(crate) struct SecretsStore {
+  provider: Box<dyn SecretsProvider>,
+  ttl:      Duration,
+  cached:   RwLock<Option<Cached>>,
+  /// Makes sure only one caller fetches from the provider at a time
+  fetching: Mutex<()>,
+  rotated:  watch::Sender<Option<Arc<Secrets>>>
+}
+
+impl SecretsStore {
+  pub(crate) fn new(config: &SecretsConfig) -> Self {
+    Self {
+      provider: config.provider(),
+      ttl:      Duration::from_secs(config.ttl),
+      cached:   RwLock::new(None),
+      fetching: Mutex::new(()),
+      rotated:  watch::channel(None).0
+    }
+  }
+
+  pub(crate) fn provider_name(&self) -> &'static str { self.provider.name() }
+
+  pub(crate) fn ttl(&self) -> Duration { self.ttl }
+
+  pub(crate) fn subscribe(&self) -> watch::Receiver<Option<Arc<Secrets>>> { self.rotated.subscribe() }
+
+  pub(crate) fn invalidate(&self) {
+    if let Some(cached) = self.cached.write().unwrap().as_mut() {
+      cached.expires = Instant::now();
+    }
+  }
+
+  fn cached(
+    &self,
+    fresh_only: bool
+  ) -> Option<Arc<Secrets>> {
+    self
+      .cached
+      .read()
+      .unwrap()
+      .as_ref()
+      .filter(|c| !fresh_only || c.expires > Instant::now())
+      .map(|c| c.secrets.clone())
+  }
+
+  /// Cached secrets if they're still fresh, otherwise fetch them.
+  /// Expired secrets are still handed out if the provider is unreachable.
+  pub(crate) async fn get(&self) -> SecretsResult<Arc<Secrets>> {
+    if let Some(secrets) = self.cached(true) {
+      return Ok(secrets);
+    }
+
+    let _fetching = self.fetching.lock().await;
+    // Someone else might've refreshed them while we were waiting
+    if let Some(secrets) = self.cached(true) {
+      return Ok(secrets);
+    }
+
+    match self.fetch().await {
+      Ok(secrets) => Ok(secrets),
+      Err(e) => match self.cached(false) {
+        Some(secrets) => {
+          warn!(provider = self.provider_name(), "{e}, using the expired secrets");
+          Ok(secrets)
+        },
+        None => Err(e)
+      }
+    }
+  }
+
+  pub(crate) async fn refresh(&self) -> SecretsResult<Arc<Secrets>> {
+    let _fetching = self.fetching.lock().await;
+    self.fetch().await
+  }
+
+  async fn fetch(&self) -> SecretsResult<Arc<Secrets>> {
+    let secrets = Arc::new(self.provider.fetch().await?);
+    let previous = self.cached.write().unwrap().replace(Cached {
+      expires: Instant::now() + self.ttl,
+      secrets: secrets.clone()
+    });
+
+    if previous.is_some_and(|p| p.secrets != secrets) {
+      info!(provider = self.provider_name(), "Secrets have been rotated");
+    }
+
+    self.rotated.send_if_modified(|current| {
+      if current.as_ref() == Some(&secrets) {
+        return false;
+      }
+      *current = Some(secrets.clone());
+      true
+    });
+
+    Ok(secrets)
+  }
+}